@@ -0,0 +1,387 @@
+//! # Utility-Programming Derive: Derive macros for composable utility programming.
+//!
+//! This crate is used through the `derive` feature of `advancedresearch-utility_programming`.
+//! The generated code refers to the library as `::utility_programming`,
+//! which can be changed by `#[utility(crate = "...")]` on the type,
+//! e.g. `#[utility(crate = "::up")]` after `extern crate utility_programming as up;`.
+//!
+//! - `#[derive(Utility)]` sums up the utility of fields, dispatching over enum variants.
+//!   Use `#[utility(weight = ...)]` on fields or variants to multiply utility,
+//!   and `#[utility(skip)]` on fields that are not utilities.
+//! - `#[derive(Generator)]` dispatches to the generator of the active enum variant,
+//!   or forwards to the field of a newtype struct.
+//! - `#[derive(Modifier)]` dispatches to the modifier of the active enum variant,
+//!   recording the variant in a generated `<Name>Change` enum.
+//!   Use `#[utility(serde)]` to derive `Serialize` and `Deserialize` for the change,
+//!   which requires the `serde` feature of the library.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Attribute, Data, DeriveInput, Error, Expr, Fields, Ident, LitStr, Path, Type};
+
+/// Derives `Utility` by summing up the utility of fields.
+#[proc_macro_derive(Utility, attributes(utility))]
+pub fn derive_utility(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    utility(&input).unwrap_or_else(compile_error).into()
+}
+
+/// Derives `Generator` by dispatching to enum variants.
+#[proc_macro_derive(Generator, attributes(utility))]
+pub fn derive_generator(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    generator(&input).unwrap_or_else(compile_error).into()
+}
+
+/// Derives `Modifier` by dispatching to enum variants.
+#[proc_macro_derive(Modifier, attributes(utility))]
+pub fn derive_modifier(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    modifier(&input).unwrap_or_else(compile_error).into()
+}
+
+/// Reports errors without referring to `::core`, which is not in scope in edition 2015.
+fn compile_error(err: Error) -> TokenStream2 {
+    err.into_iter().map(|err| {
+        let message = err.to_string();
+        quote_spanned!(err.span()=> compile_error!(#message);)
+    }).collect()
+}
+
+/// Options from `#[utility(...)]` attributes.
+struct Options {
+    weight: Option<Expr>,
+    skip: bool,
+}
+
+fn options(attrs: &[Attribute]) -> syn::Result<Options> {
+    let mut options = Options {weight: None, skip: false};
+    for attr in attrs {
+        if !attr.path().is_ident("utility") {continue}
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("weight") {
+                options.weight = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `weight` or `skip`"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+/// Options from `#[utility(...)]` attributes on the type.
+struct Container {
+    /// The path to the library.
+    krate: Path,
+    /// Whether to derive `Serialize` and `Deserialize` for the change.
+    serde: bool,
+}
+
+fn container(attrs: &[Attribute]) -> syn::Result<Container> {
+    let mut container = Container {krate: syn::parse_quote!(::utility_programming), serde: false};
+    for attr in attrs {
+        if !attr.path().is_ident("utility") {continue}
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                container.krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                Ok(())
+            } else if meta.path.is_ident("serde") {
+                container.serde = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `crate` or `serde`"))
+            }
+        })?;
+    }
+    Ok(container)
+}
+
+/// Returns a pattern binding every field by reference, with the bound names.
+fn bind(fields: &Fields) -> (TokenStream2, Vec<Ident>) {
+    let names: Vec<Ident> = (0..fields.len())
+        .map(|i| Ident::new(&format!("__f{}", i), Span::call_site()))
+        .collect();
+    let pattern = match *fields {
+        Fields::Named(ref named) => {
+            let idents = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+            quote!({#(#idents: ref #names),*})
+        }
+        Fields::Unnamed(_) => quote!((#(ref #names),*)),
+        Fields::Unit => quote!(),
+    };
+    (pattern, names)
+}
+
+/// Returns the single field type of a variant or struct.
+fn single(fields: &Fields, span: Span) -> syn::Result<Type> {
+    if fields.len() != 1 {
+        return Err(Error::new(span, "expected exactly one field"));
+    }
+    Ok(fields.iter().next().unwrap().ty.clone())
+}
+
+/// Sums the weighted utility of fields bound to names.
+fn sum(
+    krate: &Path,
+    fields: &Fields,
+    names: &[Ident],
+    types: &mut Vec<Type>
+) -> syn::Result<TokenStream2> {
+    let mut terms = vec![];
+    for (field, name) in fields.iter().zip(names) {
+        let options = options(&field.attrs)?;
+        if options.skip {continue}
+        types.push(field.ty.clone());
+        let term = quote!(#krate::Utility::utility(#name, obj));
+        terms.push(match options.weight {
+            Some(weight) => quote!((#weight) as f64 * #term),
+            None => term,
+        });
+    }
+    Ok(quote!(0.0 #(+ #terms)*))
+}
+
+fn utility(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let krate = container(&input.attrs)?.krate;
+    let mut types = vec![];
+    let body = match input.data {
+        Data::Struct(ref data) => {
+            let (pattern, names) = bind(&data.fields);
+            let sum = sum(&krate, &data.fields, &names, &mut types)?;
+            quote!(match *self {#name #pattern => #sum})
+        }
+        Data::Enum(ref data) => {
+            let mut arms = vec![];
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let (pattern, names) = bind(&variant.fields);
+                let sum = sum(&krate, &variant.fields, &names, &mut types)?;
+                let value = match options(&variant.attrs)?.weight {
+                    Some(weight) => quote!((#weight) as f64 * (#sum)),
+                    None => sum,
+                };
+                arms.push(quote!(#name::#ident #pattern => #value));
+            }
+            quote!(match *self {#(#arms,)*})
+        }
+        Data::Union(_) => return Err(Error::new(Span::call_site(), "unions are not supported")),
+    };
+    let mut generics = input.generics.clone();
+    generics.params.push(syn::parse_quote!(__T));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let bounds = where_bounds(where_clause, &types, quote!(#krate::Utility<__T>));
+    Ok(quote! {
+        impl #impl_generics #krate::Utility<__T> for #name #ty_generics #bounds {
+            #[allow(unused_variables)]
+            fn utility(&self, obj: &__T) -> f64 {#body}
+        }
+    })
+}
+
+/// Extends a where clause with a bound on each type.
+fn where_bounds(
+    where_clause: Option<&syn::WhereClause>,
+    types: &[Type],
+    bound: TokenStream2
+) -> TokenStream2 {
+    let existing = where_clause.map(|w| {
+        let predicates = &w.predicates;
+        quote!(#predicates,)
+    });
+    quote!(where #existing #(#types: #bound,)*)
+}
+
+/// Returns the variant names and field types of an enum of newtype variants,
+/// or a single variant named after a newtype struct.
+fn newtypes(input: &DeriveInput) -> syn::Result<Vec<(Option<&Ident>, TokenStream2, Type)>> {
+    match input.data {
+        Data::Struct(ref data) => {
+            Ok(vec![(None, field_pattern(&data.fields), single(&data.fields, Span::call_site())?)])
+        }
+        Data::Enum(ref data) => {
+            let mut list = vec![];
+            for variant in &data.variants {
+                let ty = single(&variant.fields, variant.ident.span())?;
+                list.push((Some(&variant.ident), field_pattern(&variant.fields), ty));
+            }
+            Ok(list)
+        }
+        Data::Union(_) => Err(Error::new(Span::call_site(), "unions are not supported")),
+    }
+}
+
+/// Returns a pattern binding a single field mutably as `__m`.
+fn field_pattern(fields: &Fields) -> TokenStream2 {
+    match *fields {
+        Fields::Named(ref named) => {
+            let ident = named.named.iter().next().unwrap().ident.as_ref().unwrap();
+            quote!({#ident: ref mut __m})
+        }
+        _ => quote!((ref mut __m)),
+    }
+}
+
+fn generator(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let krate = container(&input.attrs)?.krate;
+    let list = newtypes(input)?;
+    if list.is_empty() {
+        return Err(Error::new(Span::call_site(), "expected at least one variant"));
+    }
+    let first = &list[0].2;
+    let output = quote!(<#first as #krate::Generator>::Output);
+    let paths: Vec<TokenStream2> = list.iter().map(|&(variant, ref pattern, _)| {
+        match variant {
+            Some(variant) => quote!(#name::#variant #pattern),
+            None => quote!(#name #pattern),
+        }
+    }).collect();
+    let rest: Vec<&Type> = list[1..].iter().map(|it| &it.2).collect();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let existing = where_clause.map(|w| {
+        let predicates = &w.predicates;
+        quote!(#predicates,)
+    });
+    Ok(quote! {
+        #[allow(unused_parens)]
+        impl #impl_generics #krate::Generator for #name #ty_generics
+            where #existing #first: #krate::Generator,
+                #(#rest: #krate::Generator<Output = #output>,)*
+        {
+            type Output = #output;
+            fn generate(&mut self) -> Self::Output {
+                match *self {
+                    #(#paths => #krate::Generator::generate(__m),)*
+                }
+            }
+            fn generate_with(
+                &mut self,
+                rng: &mut dyn (#krate::RngCore)
+            ) -> Self::Output {
+                match *self {
+                    #(#paths => #krate::Generator::generate_with(__m, rng),)*
+                }
+            }
+        }
+    })
+}
+
+fn modifier(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    if let Data::Enum(_) = input.data {} else {
+        return Err(Error::new(Span::call_site(), "expected an enum"));
+    }
+    let Container {krate, serde} = container(&input.attrs)?;
+    let list = newtypes(input)?;
+    let vis = &input.vis;
+    let change = Ident::new(&format!("{}Change", name), name.span());
+    let variants: Vec<&Ident> = list.iter().map(|it| it.0.unwrap()).collect();
+    let patterns: Vec<&TokenStream2> = list.iter().map(|it| &it.1).collect();
+    let types: Vec<Type> = list.iter().map(|it| it.2.clone()).collect();
+    let params: Vec<Ident> = (0..list.len())
+        .map(|i| Ident::new(&format!("__C{}", i), Span::call_site()))
+        .collect();
+    let change_types = types.iter()
+        .map(|ty| quote!(<#ty as #krate::Modifier<__T>>::Change));
+    let doc = format!("Stores a change made by `{}`.", name);
+
+    let mut generics = input.generics.clone();
+    generics.params.push(syn::parse_quote!(__T));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let bounds = where_bounds(where_clause, &types, quote!(#krate::Modifier<__T>));
+    let message = format!("change does not match the variant of `{}`", name);
+    let derive_serde = if serde {
+        let path = quote!(#krate::serde).to_string().replace(' ', "");
+        quote! {
+            #[derive(#krate::serde::Serialize, #krate::serde::Deserialize)]
+            #[serde(crate = #path)]
+        }
+    } else {
+        quote!()
+    };
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        #derive_serde
+        #vis enum #change<#(#params),*> {
+            #(
+                #[allow(missing_docs)]
+                #variants(#params),
+            )*
+        }
+
+        #[allow(unused_parens)]
+        impl #impl_generics #krate::Modifier<__T> for #name #ty_generics #bounds {
+            type Change = #change<#(#change_types),*>;
+            fn modify(&mut self, obj: &mut __T) -> Self::Change {
+                match *self {
+                    #(#name::#variants #patterns =>
+                        #change::#variants(#krate::Modifier::modify(__m, obj)),)*
+                }
+            }
+            fn modify_with(
+                &mut self,
+                obj: &mut __T,
+                rng: &mut dyn (#krate::RngCore)
+            ) -> Self::Change {
+                match *self {
+                    #(#name::#variants #patterns => #change::#variants(
+                        #krate::Modifier::modify_with(__m, obj, rng)),)*
+                }
+            }
+            fn undo(&mut self, change: &Self::Change, obj: &mut __T) {
+                match (self, change) {
+                    #((&mut #name::#variants #patterns, &#change::#variants(ref __c)) =>
+                        #krate::Modifier::undo(__m, __c, obj),)*
+                    #[allow(unreachable_patterns)]
+                    _ => panic!(#message),
+                }
+            }
+            fn redo(&mut self, change: &Self::Change, obj: &mut __T) {
+                match (self, change) {
+                    #((&mut #name::#variants #patterns, &#change::#variants(ref __c)) =>
+                        #krate::Modifier::redo(__m, __c, obj),)*
+                    #[allow(unreachable_patterns)]
+                    _ => panic!(#message),
+                }
+            }
+            fn undo_meaning(&mut self, change: &Self::Change) {
+                match (self, change) {
+                    #((&mut #name::#variants #patterns, &#change::#variants(ref __c)) =>
+                        #krate::Modifier::undo_meaning(__m, __c),)*
+                    #[allow(unreachable_patterns)]
+                    _ => {}
+                }
+            }
+            fn redo_meaning(&mut self, change: &Self::Change) {
+                match (self, change) {
+                    #((&mut #name::#variants #patterns, &#change::#variants(ref __c)) =>
+                        #krate::Modifier::redo_meaning(__m, __c),)*
+                    #[allow(unreachable_patterns)]
+                    _ => {}
+                }
+            }
+            fn feedback(&mut self, change: &Self::Change, delta: f64) {
+                match (self, change) {
+                    #((&mut #name::#variants #patterns, &#change::#variants(ref __c)) =>
+                        #krate::Modifier::feedback(__m, __c, delta),)*
+                    #[allow(unreachable_patterns)]
+                    _ => {}
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,79 @@
+/*
+
+utility_programming: checkpoint example
+==============================================
+Interrupts an optimization run, saves a checkpoint and resumes it.
+
+Run with `cargo run --example checkpoint --features serde`.
+
+The checkpoint stores the best changes, the random number generator,
+the statistics of the adaptive modifier and the termination counters.
+The resumed run continues exactly where it stopped,
+such that it finds the same tour as a run without interruption.
+
+*/
+
+extern crate utility_programming as up;
+extern crate rand;
+
+use std::fs;
+
+use rand::SeedableRng;
+use rand::prng::XorShiftRng;
+use up::adaptive::Strategy;
+use up::journal::Journal;
+use up::permutation::RandomPermutation;
+use up::{
+    AdaptiveModifier, Checkpoint, Generator, ModifyOptimizer,
+    PermutationModifier, Termination, Tsp,
+};
+
+const TRIES: usize = 2000;
+
+fn main() {
+    let tsp = Tsp::parse(include_str!("data/burma14.tsp")).expect("Could not read instance");
+    let start = RandomPermutation(tsp.dimension).generate_with(&mut XorShiftRng::seed_from_u64(0));
+    let optimizer = ModifyOptimizer {
+        modifier: AdaptiveModifier::new(vec![
+            PermutationModifier::TwoOpt,
+            PermutationModifier::OrOpt,
+            PermutationModifier::Swap,
+        ], Strategy::Ucb1 {exploration: 1.0}),
+        utility: &tsp,
+        tries: TRIES,
+        depth: 10,
+        termination: Termination::Evaluations(TRIES * 8),
+        observer: (),
+    };
+
+    let mut tour = start.clone();
+    let (changes, stop) = optimizer.clone().optimize(&mut tour, &mut XorShiftRng::seed_from_u64(1));
+    println!("Without interruption: length {}, {:?}", tsp.tour_length(&tour), stop);
+
+    let path = std::env::temp_dir().join("utility_programming_checkpoint.json");
+    let mut interrupted = start.clone();
+    {
+        let mut optimizer = optimizer.clone();
+        let mut steps = optimizer.steps_with(&mut interrupted, XorShiftRng::seed_from_u64(1));
+        for _ in steps.by_ref().take(TRIES / 2) {}
+        steps.checkpoint().save(&path).expect("Could not save checkpoint");
+        println!("Interrupted after {} evaluations", steps.progress().evaluations);
+    }
+
+    let checkpoint: Checkpoint<_, AdaptiveModifier<PermutationModifier>, XorShiftRng> =
+        Checkpoint::load(&path).expect("Could not load checkpoint");
+    let mut best = start.clone();
+    Journal::from(checkpoint.best.clone()).replay(&mut optimizer.modifier.clone(), &mut best);
+    println!("Best so far: length {}", tsp.tour_length(&best));
+    println!("Statistics: {:?}", checkpoint.modifier.arms);
+
+    let mut resumed = start.clone();
+    let mut optimizer = optimizer;
+    let mut steps = optimizer.resume(&mut resumed, checkpoint);
+    for _ in &mut steps {}
+    let (resumed_changes, stop) = steps.finish();
+    println!("Resumed: length {}, {:?}", tsp.tour_length(&resumed), stop);
+    assert_eq!(changes, resumed_changes);
+    assert_eq!(tour, resumed);
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,138 @@
+/*
+
+utility_programming: derive example
+==============================================
+Demonstrates the derive macros of the `derive` feature.
+
+Run with `cargo run --example derive --features derive`.
+
+Utility is the weighted sum of fields, while generators and modifiers
+dispatch to the active variant of an enum.
+
+Since the library is imported as `up`, the derived types use `#[utility(crate = "::up")]`.
+With the `serde` feature, the changes of the derived modifier are serializable.
+
+*/
+
+extern crate utility_programming as up;
+extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+use up::{Generator, Modifier, ModifyOptimizer, Termination, Utility};
+
+/// Rewards being close to a target value.
+pub struct Target(pub i32);
+
+impl Utility<i32> for Target {
+    fn utility(&self, obj: &i32) -> f64 {-(*obj - self.0).abs() as f64}
+}
+
+/// Rewards even numbers.
+pub struct Even;
+
+impl Utility<i32> for Even {
+    fn utility(&self, obj: &i32) -> f64 {if *obj % 2 == 0 {1.0} else {0.0}}
+}
+
+/// Combines utilities by summing fields.
+#[derive(Utility)]
+#[utility(crate = "::up")]
+pub struct NumberUtility {
+    pub target: Target,
+    #[utility(weight = 3.0)]
+    pub even: Even,
+    #[utility(skip)]
+    pub name: &'static str,
+}
+
+/// Generates a random number in a range.
+pub struct Range(pub i32, pub i32);
+
+impl Generator for Range {
+    type Output = i32;
+    fn generate(&mut self) -> i32 {self.generate_with(&mut rand::thread_rng())}
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> i32 {rng.gen_range(self.0, self.1)}
+}
+
+/// Generates a fixed number.
+pub struct Fixed(pub i32);
+
+impl Generator for Fixed {
+    type Output = i32;
+    fn generate(&mut self) -> i32 {self.0}
+}
+
+/// Picks how to generate the initial number.
+#[derive(Generator)]
+#[utility(crate = "::up")]
+pub enum NumberGenerator {
+    Range(Range),
+    Fixed(Fixed),
+}
+
+/// Adds a step to the number.
+pub struct Step(pub i32);
+
+impl Modifier<i32> for Step {
+    type Change = i32;
+    fn modify(&mut self, obj: &mut i32) -> i32 {
+        *obj += self.0;
+        self.0
+    }
+    fn undo(&mut self, change: &i32, obj: &mut i32) {*obj -= *change}
+    fn redo(&mut self, change: &i32, obj: &mut i32) {*obj += *change}
+}
+
+/// Negates the number.
+pub struct Negate;
+
+impl Modifier<i32> for Negate {
+    type Change = ();
+    fn modify(&mut self, obj: &mut i32) {*obj = -*obj}
+    fn undo(&mut self, _: &(), obj: &mut i32) {*obj = -*obj}
+    fn redo(&mut self, _: &(), obj: &mut i32) {*obj = -*obj}
+}
+
+/// Modifies the number, generating `NumberModifierChange`.
+#[derive(Modifier)]
+#[utility(crate = "::up")]
+#[cfg_attr(feature = "serde", utility(serde))]
+pub enum NumberModifier {
+    Step(Step),
+    Negate(Negate),
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let utility = NumberUtility {target: Target(-7), even: Even, name: "near -7 and even"};
+
+    let mut num = vec![
+        NumberGenerator::Range(Range(-20, 20)),
+        NumberGenerator::Fixed(Fixed(15)),
+    ].generate_with(&mut rng);
+    println!("Starting at: {}, utility {}", num, utility.utility(&num));
+
+    let mut optimizer = ModifyOptimizer {
+        modifier: vec![
+            NumberModifier::Step(Step(1)),
+            NumberModifier::Step(Step(-1)),
+            NumberModifier::Negate(Negate),
+        ],
+        utility,
+        depth: 20,
+        tries: 1000,
+        termination: Termination::Never,
+        observer: (),
+    };
+    let (change, _) = optimizer.optimize(&mut num, &mut rng);
+    println!("Optimized to: {} ({}), utility {}",
+        num, optimizer.utility.name, optimizer.utility.utility(&num));
+    if let Some(&(_, first)) = change.first() {
+        println!("First change: {:?}", first);
+    }
+    #[cfg(feature = "serde")]
+    println!("Serialized: {}", serde_json::to_string(&change).expect("Could not serialize"));
+}
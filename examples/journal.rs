@@ -0,0 +1,51 @@
+/*
+
+utility_programming: journal example
+==============================================
+Saves the changes of an optimization run and replays them on a fresh object.
+
+Run with `cargo run --example journal --features serde`.
+
+Each round of `ModifyOptimizer` is recorded as one line in the journal,
+which is written to a temporary file and printed.
+Replaying the loaded journal from the same start reproduces the optimized tour.
+
+*/
+
+extern crate utility_programming as up;
+extern crate rand;
+
+use std::fs;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use up::journal::Journal;
+use up::permutation::RandomPermutation;
+use up::{Generator, Modifier, ModifyOptimizer, PermutationModifier, Tsp};
+
+fn main() {
+    let tsp = Tsp::parse(include_str!("data/burma14.tsp")).expect("Could not read instance");
+    let mut rng = StdRng::seed_from_u64(0);
+    let start = RandomPermutation(tsp.dimension).generate_with(&mut rng);
+    let modifier = vec![PermutationModifier::TwoOpt, PermutationModifier::OrOpt];
+    let mut optimizer = ModifyOptimizer::new(modifier, &tsp, 100, 5);
+    let mut fresh = optimizer.clone();
+
+    let mut tour = start.clone();
+    let mut journal = Journal::new();
+    for _ in 0..5 {
+        journal.record(optimizer.modify_with(&mut tour, &mut rng));
+    }
+    println!("Optimized tour length {}", tsp.tour_length(&tour));
+
+    let path = std::env::temp_dir().join("utility_programming_journal.jsonl");
+    journal.save(&path).expect("Could not save journal");
+    println!("Journal:\n{}", fs::read_to_string(&path).expect("Could not read journal"));
+
+    let loaded = Journal::load(&path).expect("Could not load journal");
+    let mut replayed = start.clone();
+    loaded.replay(&mut fresh, &mut replayed);
+    println!("Replayed tour length {}", tsp.tour_length(&replayed));
+    assert_eq!(tour, replayed);
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,77 @@
+/*
+
+utility_programming: laws example
+==============================================
+Checks that modifiers satisfy the laws of `undo` and `redo`.
+
+Run with `cargo run --example laws --features testing`.
+
+The standard list modifiers pass.
+A broken modifier is reported with a minimal failing sequence of changes.
+The checks of all standard modifiers are in `tests/laws.rs`.
+
+*/
+
+extern crate utility_programming as up;
+extern crate rand;
+
+use rand::{Rng, RngCore};
+use up::list::ListOp;
+use up::testing::{check, Laws};
+use up::{Generator, ListModifier, Modifier};
+
+/// Generates a random small number.
+#[derive(Clone)]
+pub struct Small;
+
+impl Generator for Small {
+    type Output = i32;
+    fn generate(&mut self) -> i32 {self.generate_with(&mut rand::thread_rng())}
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> i32 {rng.gen_range(-10, 10)}
+}
+
+/// Generates a list of random small numbers.
+#[derive(Clone)]
+pub struct SmallList;
+
+impl Generator for SmallList {
+    type Output = Vec<i32>;
+    fn generate(&mut self) -> Vec<i32> {self.generate_with(&mut rand::thread_rng())}
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Vec<i32> {
+        let n = rng.gen_range(0, 6);
+        (0..n).map(|_| Small.generate_with(rng)).collect()
+    }
+}
+
+/// Increments a number up to 3, but undoes by decrementing, which is wrong at the bound.
+#[derive(Clone)]
+pub struct Bounded;
+
+impl Modifier<i32> for Bounded {
+    type Change = ();
+    fn modify(&mut self, obj: &mut i32) {*obj = (*obj + 1).min(3)}
+    fn undo(&mut self, _: &(), obj: &mut i32) {*obj -= 1}
+    fn redo(&mut self, _: &(), obj: &mut i32) {*obj = (*obj + 1).min(3)}
+}
+
+fn main() {
+    let laws = Laws::default();
+
+    let list = vec![
+        ListModifier::new(ListOp::Insert(Small)),
+        ListModifier::new(ListOp::Remove),
+        ListModifier::new(ListOp::Swap),
+        ListModifier::new(ListOp::Move).within(1..3),
+        ListModifier::new(ListOp::Reverse),
+        ListModifier::new(ListOp::Replace(Small)),
+    ];
+    match check(&mut SmallList, &list, &laws) {
+        Ok(()) => println!("List modifiers pass"),
+        Err(failure) => println!("List modifiers fail: {}", failure),
+    }
+
+    match check(&mut Small, &Bounded, &laws) {
+        Ok(()) => println!("Bounded passes"),
+        Err(failure) => println!("Bounded fails: {}", failure),
+    }
+}
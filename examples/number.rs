@@ -18,7 +18,9 @@ but it depends on whether there are other conflicting features.
 extern crate utility_programming as up;
 extern crate rand;
 
-use up::{Generator, Modifier, ModifyOptimizer, Utility};
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+use up::{Generator, Modifier, ModifyOptimizer, Termination, Utility};
 
 /// Computes utility of a number.
 pub enum NumberUtility {
@@ -33,6 +35,8 @@ pub enum NumberUtility {
 }
 
 impl Utility<u8> for NumberUtility {
+    // `is_multiple_of` requires a newer Rust than the crate supports.
+    #[allow(clippy::manual_is_multiple_of)]
     fn utility(&self, obj: &u8) -> f64 {
         match *self {
             NumberUtility::Target {value, penalty} => {
@@ -61,8 +65,11 @@ pub enum NumberGenerator {
 impl Generator for NumberGenerator {
     type Output = u8;
     fn generate(&mut self) -> Self::Output {
+        self.generate_with(&mut rand::thread_rng())
+    }
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Self::Output {
         match *self {
-            NumberGenerator::Random => rand::random::<u8>(),
+            NumberGenerator::Random => rng.gen::<u8>(),
             NumberGenerator::Fixed(val) => val,
         }
     }
@@ -106,13 +113,16 @@ impl Modifier<u8> for NumberModifier {
 }
 
 fn main() {
+    // Use a fixed seed to replay the same optimization run.
+    let mut rng = StdRng::seed_from_u64(0);
+
     // Generate a number.
     // A random generator is picked when using a list of generators.
     let mut num = vec![
         NumberGenerator::Random,
         NumberGenerator::Fixed(100),
         NumberGenerator::Fixed(0),
-    ].generate();
+    ].generate_with(&mut rng);
 
     let target = 42;
 
@@ -122,18 +132,23 @@ fn main() {
             NumberModifier::Inc,
             NumberModifier::Dec
         ],
+        // Remember the utility of visited numbers, since modifications often cancel each other.
         utility: vec![
             NumberUtility::Target {value: target, penalty: -1.0},
             NumberUtility::Prime {reward: 5.0},
-        ],
+        ].cached(|num: &u8| *num as u64, 256),
         // Make sure that the optimizer is likely to make progress when possible.
         depth: 20,
         tries: 1000,
+        termination: Termination::Never,
+        observer: (),
     };
     loop {
         println!("{}, utility {}", num, optimizer.utility.utility(&num));
         let old = num;
-        optimizer.modify(&mut num);
+        optimizer.modify_with(&mut num, &mut rng);
         if num == old {break}
     }
+    let stats = optimizer.utility.stats();
+    println!("Cache hits {}, misses {}", stats.hits, stats.misses);
 }
@@ -0,0 +1,95 @@
+/*
+
+utility_programming: tsp example
+==============================================
+Compares optimizers on a traveling salesman problem.
+
+Run with `cargo run --release --example tsp [path/to/instance.tsp]`.
+By default, the bundled instance `burma14` is used, which has optimal tour length 3323.
+
+Every optimizer gets the same budget of utility evaluations
+and starts from the same random tour.
+Reversals and swaps are evaluated from the change in length of the edges at their ends.
+
+*/
+
+extern crate utility_programming as up;
+extern crate rand;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use up::annealing::Geometric;
+use up::observer::Counter;
+use up::permutation::RandomPermutation;
+use up::tabu::Hashed;
+use up::{
+    Evaluation, Generator, ModifyOptimizer, PermutationModifier, SimulatedAnnealing,
+    TabuSearch, Termination, Tsp,
+};
+
+const BUDGET: usize = 20_000;
+
+fn modifiers() -> Vec<PermutationModifier> {
+    vec![
+        PermutationModifier::TwoOpt,
+        PermutationModifier::ThreeOpt,
+        PermutationModifier::OrOpt,
+        PermutationModifier::Swap,
+        PermutationModifier::Insertion,
+        PermutationModifier::Scramble(4),
+    ]
+}
+
+fn report(name: &str, tsp: &Tsp, tour: &[usize], counter: &Counter) {
+    println!("{:<22} length {:>8} after {} evaluations",
+        name, tsp.tour_length(tour), counter.evaluations);
+}
+
+fn main() {
+    let tsp = match std::env::args().nth(1) {
+        Some(path) => Tsp::read(path),
+        None => Tsp::parse(include_str!("data/burma14.tsp")),
+    }.expect("Could not read instance");
+    let mut rng = StdRng::seed_from_u64(0);
+    let start = RandomPermutation(tsp.dimension).generate_with(&mut rng);
+    println!("{} with {} cities, start length {}", tsp.name, tsp.dimension, tsp.tour_length(&start));
+
+    let mut tour = start.clone();
+    let mut optimizer = ModifyOptimizer {
+        modifier: modifiers(),
+        utility: tsp.clone(),
+        tries: BUDGET,
+        depth: 10,
+        termination: Termination::Evaluations(BUDGET),
+        observer: Counter::default(),
+    };
+    optimizer.optimize_by(&mut tour, &mut rng, Evaluation::Delta);
+    report("ModifyOptimizer", &tsp, &tour, &optimizer.observer);
+
+    let mut tour = start.clone();
+    let mut optimizer = SimulatedAnnealing {
+        modifier: modifiers(),
+        utility: tsp.clone(),
+        cooling: Geometric {factor: 0.9995},
+        temperature: 100.0,
+        steps: BUDGET,
+        termination: Termination::Evaluations(BUDGET),
+        observer: Counter::default(),
+    };
+    optimizer.optimize_by(&mut tour, &mut rng, Evaluation::Delta);
+    report("SimulatedAnnealing", &tsp, &tour, &optimizer.observer);
+
+    let mut tour = start.clone();
+    let mut optimizer = TabuSearch {
+        modifier: modifiers(),
+        utility: tsp.clone(),
+        key: Hashed,
+        tenure: 50,
+        neighbors: 20,
+        iterations: BUDGET,
+        termination: Termination::Evaluations(BUDGET),
+        observer: Counter::default(),
+    };
+    optimizer.optimize_by(&mut tour, &mut rng, Evaluation::Delta);
+    report("TabuSearch", &tsp, &tour, &optimizer.observer);
+}
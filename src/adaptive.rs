@@ -0,0 +1,184 @@
+//! Adaptive operator selection.
+
+use rand::{Rng, RngCore};
+use rand::distributions::{Distribution, Gamma};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use float;
+use Modifier;
+
+/// Strategy for selecting a modifier based on past feedback.
+///
+/// A modification is counted as a success when it increases utility.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Strategy {
+    /// Upper confidence bound, trading off success rate against uncertainty.
+    Ucb1 {
+        /// Scales the exploration term, usually `1.0`.
+        #[cfg_attr(feature = "serde", serde(with = "float"))]
+        exploration: f64,
+    },
+    /// Picks the best success rate, but a random modifier with some probability.
+    EpsilonGreedy {
+        /// The probability of picking a random modifier.
+        #[cfg_attr(feature = "serde", serde(with = "float"))]
+        epsilon: f64,
+    },
+    /// Samples success rates from Beta distributions and picks the best.
+    Thompson,
+    /// Picks with probability proportional to estimated quality.
+    ProbabilityMatching {
+        /// The minimum probability of picking any modifier.
+        #[cfg_attr(feature = "serde", serde(with = "float"))]
+        min: f64,
+        /// The adaptation rate of quality, between `0.0` and `1.0`.
+        #[cfg_attr(feature = "serde", serde(with = "float"))]
+        rate: f64,
+    },
+}
+
+/// Statistics learned about a modifier.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Arm {
+    /// The number of times the modifier was picked.
+    pub pulls: usize,
+    /// The number of modifications that increased utility.
+    pub successes: usize,
+    /// The sum of utility differences.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub total_delta: f64,
+    /// The estimated quality used by probability matching.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub quality: f64,
+}
+
+impl Arm {
+    /// Returns the ratio of successes to pulls.
+    pub fn success_rate(&self) -> f64 {
+        if self.pulls == 0 {0.0} else {self.successes as f64 / self.pulls as f64}
+    }
+
+    /// Returns the average utility difference.
+    pub fn mean_delta(&self) -> f64 {
+        if self.pulls == 0 {0.0} else {self.total_delta / self.pulls as f64}
+    }
+}
+
+/// Learns which modifier tends to improve utility.
+///
+/// This works like `Vec<T: Modifier>`, but picks modifiers using a multi-armed bandit strategy.
+/// The statistics are updated from `Modifier::feedback`, which is called by optimizers,
+/// so the modifier does not learn when used outside an optimizer.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AdaptiveModifier<U> {
+    /// The modifiers to pick from.
+    pub modifiers: Vec<U>,
+    /// The selection strategy.
+    pub strategy: Strategy,
+    /// Learned statistics, one per modifier.
+    pub arms: Vec<Arm>,
+}
+
+impl<U> AdaptiveModifier<U> {
+    /// Creates a new adaptive modifier without statistics.
+    pub fn new(modifiers: Vec<U>, strategy: Strategy) -> AdaptiveModifier<U> {
+        let arms = modifiers.iter().map(|_| Arm::default()).collect();
+        AdaptiveModifier {modifiers, strategy, arms}
+    }
+
+    /// Picks a modifier index.
+    pub fn pick(&self, rng: &mut dyn RngCore) -> usize {
+        let n = self.arms.len();
+        match self.strategy {
+            Strategy::Ucb1 {exploration} => {
+                if let Some(i) = self.arms.iter().position(|arm| arm.pulls == 0) {return i}
+                let total: usize = self.arms.iter().map(|arm| arm.pulls).sum();
+                let ln = (total as f64).ln();
+                argmax(self.arms.iter().map(|arm| {
+                    arm.success_rate() + exploration * (2.0 * ln / arm.pulls as f64).sqrt()
+                }))
+            }
+            Strategy::EpsilonGreedy {epsilon} => {
+                if rng.gen::<f64>() < epsilon {
+                    rng.gen_range(0, n)
+                } else {
+                    argmax(self.arms.iter().map(|arm| arm.success_rate()))
+                }
+            }
+            Strategy::Thompson => {
+                let scores: Vec<f64> = self.arms.iter().map(|arm| {
+                    let a = Gamma::new(1.0 + arm.successes as f64, 1.0).sample(rng);
+                    let b = Gamma::new(1.0 + (arm.pulls - arm.successes) as f64, 1.0).sample(rng);
+                    a / (a + b)
+                }).collect();
+                argmax(scores.into_iter())
+            }
+            Strategy::ProbabilityMatching {min, ..} => {
+                let total: f64 = self.arms.iter().map(|arm| arm.quality).sum();
+                let mut r = rng.gen::<f64>();
+                for (i, arm) in self.arms.iter().enumerate() {
+                    let p = if total > 0.0 {
+                        min + (1.0 - n as f64 * min) * arm.quality / total
+                    } else {
+                        1.0 / n as f64
+                    };
+                    if r < p {return i}
+                    r -= p;
+                }
+                n - 1
+            }
+        }
+    }
+}
+
+fn argmax<I: Iterator<Item = f64>>(iter: I) -> usize {
+    let mut best = 0;
+    let mut best_value = f64::NEG_INFINITY;
+    for (i, value) in iter.enumerate() {
+        if best_value < value {
+            best = i;
+            best_value = value;
+        }
+    }
+    best
+}
+
+impl<T, U: Modifier<T>> Modifier<T> for AdaptiveModifier<U> {
+    type Change = (usize, U::Change);
+    fn modify(&mut self, obj: &mut T) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        let index = self.pick(rng);
+        (index, self.modifiers[index].modify_with(obj, rng))
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut T) {
+        self.modifiers[change.0].undo(&change.1, obj)
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut T) {
+        self.modifiers[change.0].redo(&change.1, obj)
+    }
+    fn undo_meaning(&mut self, change: &Self::Change) {
+        for it in &mut self.modifiers {it.undo_meaning(&change.1)}
+    }
+    fn redo_meaning(&mut self, change: &Self::Change) {
+        for it in &mut self.modifiers {it.redo_meaning(&change.1)}
+    }
+    fn feedback(&mut self, change: &Self::Change, delta: f64) {
+        let success = delta > 0.0;
+        let arm = &mut self.arms[change.0];
+        arm.pulls += 1;
+        if success {arm.successes += 1}
+        arm.total_delta += delta;
+        if let Strategy::ProbabilityMatching {rate, ..} = self.strategy {
+            let reward = if success {1.0} else {0.0};
+            arm.quality += rate * (reward - arm.quality);
+        }
+        self.modifiers[change.0].feedback(&change.1, delta)
+    }
+}
@@ -0,0 +1,211 @@
+//! Simulated annealing.
+
+use rand::{Rng, RngCore};
+
+use delta::{DeltaUtility, Evaluation, Evaluator};
+use {Modifier, Observer, Progress, Stop, Termination, Utility};
+
+/// Implemented by cooling schedules for simulated annealing.
+pub trait Cooling {
+    /// Computes the temperature at some step.
+    ///
+    /// - `initial` is the initial temperature
+    /// - `step` is the number of steps since the start
+    /// - `stagnation` is the number of steps since the last improvement of best utility
+    fn temperature(&mut self, initial: f64, step: usize, stagnation: usize) -> f64;
+    /// Resets the schedule before a new run.
+    fn reset(&mut self) {}
+}
+
+/// Multiplies temperature by a factor each step.
+#[derive(Copy, Clone, Debug)]
+pub struct Geometric {
+    /// The factor to multiply with, usually slightly less than `1.0`.
+    pub factor: f64,
+}
+
+impl Cooling for Geometric {
+    fn temperature(&mut self, initial: f64, step: usize, _stagnation: usize) -> f64 {
+        initial * self.factor.powf(step as f64)
+    }
+}
+
+/// Decreases temperature by a fixed amount each step, until it reaches zero.
+#[derive(Copy, Clone, Debug)]
+pub struct Linear {
+    /// The amount to decrease per step.
+    pub decrease: f64,
+}
+
+impl Cooling for Linear {
+    fn temperature(&mut self, initial: f64, step: usize, _stagnation: usize) -> f64 {
+        (initial - self.decrease * step as f64).max(0.0)
+    }
+}
+
+/// Decreases temperature by the logarithm of steps.
+///
+/// This cools slowly; it converges in probability to a global optimum
+/// only for a sufficiently large initial temperature.
+#[derive(Copy, Clone, Debug)]
+pub struct Logarithmic;
+
+impl Cooling for Logarithmic {
+    fn temperature(&mut self, initial: f64, step: usize, _stagnation: usize) -> f64 {
+        initial / (1.0 + (1.0 + step as f64).ln())
+    }
+}
+
+/// Restarts a cooling schedule when there has been no improvement for a while.
+#[derive(Clone, Debug)]
+pub struct Reheating<C> {
+    /// The cooling schedule to restart.
+    pub cooling: C,
+    /// The number of steps without improvement before reheating.
+    pub patience: usize,
+    /// The step of the last reheat.
+    pub last: usize,
+}
+
+impl<C> Reheating<C> {
+    /// Creates a new reheating schedule.
+    pub fn new(cooling: C, patience: usize) -> Reheating<C> {
+        Reheating {cooling, patience, last: 0}
+    }
+}
+
+impl<C: Cooling> Cooling for Reheating<C> {
+    fn temperature(&mut self, initial: f64, step: usize, stagnation: usize) -> f64 {
+        if stagnation >= self.patience && step - self.last >= self.patience {
+            self.last = step;
+        }
+        self.cooling.temperature(initial, step - self.last, stagnation)
+    }
+    fn reset(&mut self) {
+        self.last = 0;
+        self.cooling.reset();
+    }
+}
+
+/// Modifies an object by simulated annealing.
+///
+/// Worse changes are accepted with probability `exp(delta / temperature)`,
+/// which allows escaping local optima while the temperature is high.
+///
+/// The change is the sequence of accepted changes leading to the best seen state.
+pub struct SimulatedAnnealing<M, U, C = Geometric, O = ()> {
+    /// The modifier to modify the object.
+    pub modifier: M,
+    /// The measured utility.
+    pub utility: U,
+    /// The cooling schedule.
+    pub cooling: C,
+    /// The initial temperature.
+    pub temperature: f64,
+    /// The number of modifications.
+    pub steps: usize,
+    /// The criterion for stopping before all steps are completed.
+    pub termination: Termination,
+    /// Observes the optimization.
+    ///
+    /// A rejected modification is reported as backtracking one change.
+    pub observer: O,
+}
+
+impl<M, U, C: Cooling, O: Observer> SimulatedAnnealing<M, U, C, O> {
+    /// Modifies an object and returns the change with the reason for stopping.
+    pub fn optimize<T>(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: Utility<T>
+    {
+        self.optimize_in(obj, rng, &Evaluator::full())
+    }
+
+    /// Modifies an object, evaluating utility by a strategy,
+    /// and returns the change with the reason for stopping.
+    pub fn optimize_by<T>(
+        &mut self,
+        obj: &mut T,
+        rng: &mut dyn RngCore,
+        evaluation: Evaluation
+    ) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: DeltaUtility<T, M::Change>
+    {
+        self.optimize_in(obj, rng, &Evaluator::new(evaluation))
+    }
+
+    fn optimize_in<T>(
+        &mut self,
+        obj: &mut T,
+        rng: &mut dyn RngCore,
+        evaluator: &Evaluator<U, T, M::Change>
+    ) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: Utility<T>
+    {
+        self.cooling.reset();
+        let mut current = self.utility.utility(obj);
+        self.observer.start(current);
+        let mut progress = Progress::new(current);
+        let mut best_len = 0;
+        let mut stack = vec![];
+        let mut stop = None;
+        for step in 0..self.steps {
+            let temperature = self.cooling.temperature(self.temperature, step, progress.stagnation);
+            let change = self.modifier.modify_with(obj, rng);
+            self.modifier.redo_meaning(&change);
+            let utility = evaluator.utility(&self.utility, obj, &change, current);
+            self.modifier.feedback(&change, utility - current);
+            self.observer.change(utility);
+            let accept = utility >= current ||
+                temperature > 0.0 &&
+                rng.gen::<f64>() < ((utility - current) / temperature).exp();
+            if accept {
+                stack.push(change);
+                current = utility;
+                if progress.best_utility < utility {
+                    best_len = stack.len();
+                    self.observer.improve(utility);
+                }
+            } else {
+                self.observer.backtrack(1);
+                self.modifier.undo(&change, obj);
+                self.modifier.undo_meaning(&change);
+            }
+            progress.evaluate(utility);
+            stop = self.termination.check(&progress);
+            if stop.is_some() {break}
+        }
+        self.observer.backtrack(stack.len() - best_len);
+        while stack.len() > best_len {
+            let change = stack.pop().unwrap();
+            self.modifier.undo(&change, obj);
+            self.modifier.undo_meaning(&change);
+        }
+        let stop = stop.unwrap_or(Stop::Completed);
+        self.observer.stop(&stop);
+        (stack, stop)
+    }
+}
+
+impl<T, M, U, C, O> Modifier<T> for SimulatedAnnealing<M, U, C, O>
+    where M: Modifier<T>, U: Utility<T>, C: Cooling, O: Observer
+{
+    type Change = Vec<M::Change>;
+    fn modify(&mut self, obj: &mut T) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        self.optimize(obj, rng).0
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut T) {
+        for change in change.iter().rev() {
+            self.modifier.undo(change, obj);
+            self.modifier.undo_meaning(change);
+        }
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut T) {
+        for change in change {
+            self.modifier.redo(change, obj);
+            self.modifier.redo_meaning(change);
+        }
+    }
+}
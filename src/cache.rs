@@ -0,0 +1,150 @@
+//! Caching utility.
+//!
+//! Optimizers often revisit the same objects, e.g. by modifications that cancel each other,
+//! such that identical objects are scored repeatedly.
+//! `Cached` remembers the utility of recently measured objects by a fingerprint.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, MutexGuard};
+
+use Utility;
+
+/// Computes the standard hash of an object, for use as fingerprint.
+pub fn hashed<T: Hash>(obj: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    obj.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Statistics of a cache.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The number of utilities found in the cache.
+    pub hits: usize,
+    /// The number of utilities that were computed.
+    pub misses: usize,
+    /// The number of utilities removed to make room for new ones.
+    pub evictions: usize,
+}
+
+impl Stats {
+    /// Returns the ratio of hits to lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {0.0} else {self.hits as f64 / lookups as f64}
+    }
+}
+
+/// Remembers utilities, evicting the least recently used one when full.
+#[derive(Clone, Debug, Default)]
+struct Lru {
+    /// The utility and last use of each fingerprint.
+    entries: HashMap<u64, (f64, u64)>,
+    /// The fingerprint of each last use.
+    uses: BTreeMap<u64, u64>,
+    /// Counts uses.
+    clock: u64,
+    stats: Stats,
+}
+
+impl Lru {
+    fn get(&mut self, key: u64) -> Option<f64> {
+        self.clock += 1;
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                self.uses.remove(&entry.1);
+                self.uses.insert(self.clock, key);
+                entry.1 = self.clock;
+                self.stats.hits += 1;
+                Some(entry.0)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: u64, utility: f64, capacity: usize) {
+        if capacity == 0 {return}
+        self.clock += 1;
+        if let Some(entry) = self.entries.get_mut(&key) {
+            // Computed by another thread in the meantime.
+            self.uses.remove(&entry.1);
+            self.uses.insert(self.clock, key);
+            *entry = (utility, self.clock);
+            return;
+        }
+        while self.entries.len() >= capacity {
+            let (_, oldest) = self.uses.pop_first().unwrap();
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+        self.entries.insert(key, (utility, self.clock));
+        self.uses.insert(self.clock, key);
+    }
+}
+
+/// Caches the utility of objects by a fingerprint.
+///
+/// The fingerprint, e.g. `hashed`, is assumed to be equal only for objects of equal utility.
+/// At most `capacity` utilities are kept, evicting the least recently used one.
+/// The cache is shared behind a lock, such that it works with `utility(&self, ...)`,
+/// also when evaluating in parallel.
+pub struct Cached<U, F> {
+    /// The cached utility.
+    pub utility: U,
+    /// Computes the fingerprint of an object.
+    pub fingerprint: F,
+    /// The maximum number of cached utilities.
+    pub capacity: usize,
+    cache: Mutex<Lru>,
+}
+
+impl<U, F> Cached<U, F> {
+    /// Creates a new empty cache.
+    pub fn new(utility: U, fingerprint: F, capacity: usize) -> Cached<U, F> {
+        Cached {utility, fingerprint, capacity, cache: Mutex::new(Lru::default())}
+    }
+
+    /// Returns the statistics of hits, misses and evictions.
+    pub fn stats(&self) -> Stats {self.lock().stats}
+
+    /// Returns the number of cached utilities.
+    pub fn len(&self) -> usize {self.lock().entries.len()}
+
+    /// Returns `true` if no utilities are cached.
+    pub fn is_empty(&self) -> bool {self.len() == 0}
+
+    /// Removes all cached utilities and resets the statistics.
+    ///
+    /// Call this when the utility changes, e.g. when adjusting weights.
+    pub fn clear(&self) {*self.lock() = Lru::default()}
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<U: Clone, F: Clone> Clone for Cached<U, F> {
+    fn clone(&self) -> Cached<U, F> {
+        Cached {
+            utility: self.utility.clone(),
+            fingerprint: self.fingerprint.clone(),
+            capacity: self.capacity,
+            cache: Mutex::new(self.lock().clone()),
+        }
+    }
+}
+
+impl<T, U: Utility<T>, F: Fn(&T) -> u64> Utility<T> for Cached<U, F> {
+    fn utility(&self, obj: &T) -> f64 {
+        let key = (self.fingerprint)(obj);
+        if let Some(utility) = self.lock().get(key) {return utility}
+        let utility = self.utility.utility(obj);
+        self.lock().insert(key, utility, self.capacity);
+        utility
+    }
+}
@@ -0,0 +1,90 @@
+//! Utility combinators.
+//!
+//! These are usually constructed with the methods of `Utility`,
+//! e.g. `u.scale(2.0).max(v)`.
+
+use Utility;
+
+/// Multiplies utility by a factor.
+#[derive(Copy, Clone, Debug)]
+pub struct Scaled<U>(pub U, pub f64);
+
+impl<T, U: Utility<T>> Utility<T> for Scaled<U> {
+    fn utility(&self, obj: &T) -> f64 {self.1 * self.0.utility(obj)}
+}
+
+/// Negates utility.
+#[derive(Copy, Clone, Debug)]
+pub struct Neg<U>(pub U);
+
+impl<T, U: Utility<T>> Utility<T> for Neg<U> {
+    fn utility(&self, obj: &T) -> f64 {-self.0.utility(obj)}
+}
+
+/// Picks the least of two utilities.
+#[derive(Copy, Clone, Debug)]
+pub struct Min<A, B>(pub A, pub B);
+
+impl<T, A: Utility<T>, B: Utility<T>> Utility<T> for Min<A, B> {
+    fn utility(&self, obj: &T) -> f64 {self.0.utility(obj).min(self.1.utility(obj))}
+}
+
+/// Picks the greatest of two utilities.
+#[derive(Copy, Clone, Debug)]
+pub struct Max<A, B>(pub A, pub B);
+
+impl<T, A: Utility<T>, B: Utility<T>> Utility<T> for Max<A, B> {
+    fn utility(&self, obj: &T) -> f64 {self.0.utility(obj).max(self.1.utility(obj))}
+}
+
+/// Multiplies two utilities.
+#[derive(Copy, Clone, Debug)]
+pub struct Product<A, B>(pub A, pub B);
+
+impl<T, A: Utility<T>, B: Utility<T>> Utility<T> for Product<A, B> {
+    fn utility(&self, obj: &T) -> f64 {self.0.utility(obj) * self.1.utility(obj)}
+}
+
+/// Returns `1.0` when utility reaches a threshold, `0.0` otherwise.
+#[derive(Copy, Clone, Debug)]
+pub struct Threshold<U>(pub U, pub f64);
+
+impl<T, U: Utility<T>> Utility<T> for Threshold<U> {
+    fn utility(&self, obj: &T) -> f64 {
+        if self.0.utility(obj) >= self.1 {1.0} else {0.0}
+    }
+}
+
+/// Restricts utility to a range `[min, max]`.
+#[derive(Copy, Clone, Debug)]
+pub struct Clamp<U>(pub U, pub f64, pub f64);
+
+impl<T, U: Utility<T>> Utility<T> for Clamp<U> {
+    fn utility(&self, obj: &T) -> f64 {self.0.utility(obj).max(self.1).min(self.2)}
+}
+
+/// Takes the natural logarithm of utility.
+///
+/// This gives diminishing returns for positive utility.
+#[derive(Copy, Clone, Debug)]
+pub struct Log<U>(pub U);
+
+impl<T, U: Utility<T>> Utility<T> for Log<U> {
+    fn utility(&self, obj: &T) -> f64 {self.0.utility(obj).ln()}
+}
+
+/// Raises utility to a power.
+#[derive(Copy, Clone, Debug)]
+pub struct Pow<U>(pub U, pub f64);
+
+impl<T, U: Utility<T>> Utility<T> for Pow<U> {
+    fn utility(&self, obj: &T) -> f64 {self.0.utility(obj).powf(self.1)}
+}
+
+/// Maps utility smoothly to the range `(0, 1)`.
+#[derive(Copy, Clone, Debug)]
+pub struct Sigmoid<U>(pub U);
+
+impl<T, U: Utility<T>> Utility<T> for Sigmoid<U> {
+    fn utility(&self, obj: &T) -> f64 {1.0 / (1.0 + (-self.0.utility(obj)).exp())}
+}
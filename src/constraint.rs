@@ -0,0 +1,179 @@
+//! Hard constraints and feasibility.
+
+use rand::{Rng, RngCore};
+
+use {Modifier, Observer, Progress, Stop, Termination, Utility};
+
+/// Implemented by objects that constrain which objects are feasible.
+pub trait Constraint<T> {
+    /// Computes how much an object violates the constraint.
+    ///
+    /// This is zero when the object is feasible and positive otherwise.
+    fn violation(&self, obj: &T) -> f64;
+    /// Returns `true` if the object satisfies the constraint.
+    fn feasible(&self, obj: &T) -> bool {self.violation(obj) <= 0.0}
+}
+
+/// Sums up violation from multiple constraints.
+impl<T, C: Constraint<T>> Constraint<T> for Vec<C> {
+    fn violation(&self, obj: &T) -> f64 {
+        self.iter().map(|it| it.violation(obj)).sum()
+    }
+}
+
+/// Strategy for handling infeasible objects.
+#[derive(Copy, Clone, Debug)]
+pub enum Handling {
+    /// Rejects infeasible modifications immediately by undoing them.
+    ///
+    /// Every visited object is feasible, given that the initial object is feasible.
+    /// A rejected modification is fed back to the modifier as a change in utility of `-inf`,
+    /// such that adaptive modifiers learn to avoid it.
+    Death,
+    /// Subtracts violation multiplied by a weight from utility.
+    ///
+    /// Infeasible objects can be visited and kept if the penalty is too small.
+    Penalty(f64),
+    /// Compares by utility with some probability when either object is infeasible,
+    /// and by violation otherwise.
+    ///
+    /// Feasible objects are always compared by utility.
+    /// Since an infeasible object may win by utility, infeasible regions can be crossed.
+    /// Once a feasible object is found, the best object is only replaced by feasible objects.
+    /// A probability around `0.45` is common.
+    StochasticRanking(f64),
+}
+
+impl Handling {
+    /// Returns `true` if `a` is better than `b`, given pairs of utility and violation.
+    pub fn better(&self, a: (f64, f64), b: (f64, f64), rng: &mut dyn RngCore) -> bool {
+        match *self {
+            Handling::Death => a.1 <= 0.0 && (b.1 > 0.0 || b.0 < a.0),
+            Handling::Penalty(weight) => b.0 - weight * b.1 < a.0 - weight * a.1,
+            Handling::StochasticRanking(p) => {
+                if a.1 <= 0.0 && b.1 <= 0.0 || rng.gen::<f64>() < p {b.0 < a.0} else {a.1 < b.1}
+            }
+        }
+    }
+
+    /// Computes a single score used for termination criteria.
+    pub fn score(&self, utility: f64, violation: f64) -> f64 {
+        match *self {
+            Handling::Penalty(weight) => utility - weight * violation,
+            Handling::Death | Handling::StochasticRanking(_) => {
+                if violation > 0.0 {f64::NEG_INFINITY} else {utility}
+            }
+        }
+    }
+}
+
+/// Modifies an object using a modifier by maximizing utility subject to a constraint.
+///
+/// This works like `ModifyOptimizer`, but uses a handling strategy for infeasible objects.
+pub struct ConstrainedOptimizer<M, U, C, O = ()> {
+    /// The modifier to modify the object.
+    pub modifier: M,
+    /// The measured utility.
+    pub utility: U,
+    /// The constraint to satisfy.
+    pub constraint: C,
+    /// The strategy for handling infeasible objects.
+    pub handling: Handling,
+    /// The number of tries before giving up.
+    pub tries: usize,
+    /// The number of repeated modifications before backtracking.
+    pub depth: usize,
+    /// The criterion for stopping before all tries are completed.
+    pub termination: Termination,
+    /// Observes the optimization.
+    ///
+    /// A modification rejected by `Handling::Death` is reported as backtracking one change.
+    pub observer: O,
+}
+
+impl<M, U, C, O: Observer> ConstrainedOptimizer<M, U, C, O> {
+    /// Modifies an object and returns the change with the reason for stopping.
+    pub fn optimize<T>(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: Utility<T>, C: Constraint<T>, M::Change: Clone
+    {
+        let mut best = vec![];
+        let start = self.utility.utility(obj);
+        let mut best_pair = (start, self.constraint.violation(obj));
+        self.observer.start(start);
+        let mut progress = Progress::new(self.handling.score(best_pair.0, best_pair.1));
+        let mut stack = vec![];
+        let mut stop = None;
+        for i in 0..self.tries {
+            self.observer.next_try(i);
+            let mut previous = start;
+            for _ in 0..self.depth {
+                let change = self.modifier.modify_with(obj, rng);
+                self.modifier.redo_meaning(&change);
+                let violation = self.constraint.violation(obj);
+                if let (Handling::Death, true) = (self.handling, violation > 0.0) {
+                    self.modifier.feedback(&change, f64::NEG_INFINITY);
+                    self.modifier.undo(&change, obj);
+                    self.modifier.undo_meaning(&change);
+                    self.observer.backtrack(1);
+                } else {
+                    let utility = self.utility.utility(obj);
+                    self.modifier.feedback(&change, utility - previous);
+                    previous = utility;
+                    stack.push(change);
+                    self.observer.change(utility);
+                    let improved = match self.handling {
+                        Handling::StochasticRanking(_) if best_pair.1 <= 0.0 => {
+                            violation <= 0.0 && best_pair.0 < utility
+                        }
+                        _ => self.handling.better((utility, violation), best_pair, rng),
+                    };
+                    if improved {
+                        best = stack.clone();
+                        best_pair = (utility, violation);
+                        self.observer.improve(utility);
+                    }
+                    progress.evaluate(self.handling.score(utility, violation));
+                }
+                stop = self.termination.check(&progress);
+                if stop.is_some() {break}
+            }
+            self.observer.backtrack(stack.len());
+            while let Some(ref action) = stack.pop() {
+                self.modifier.undo(action, obj);
+                self.modifier.undo_meaning(action);
+            }
+            if stop.is_some() {break}
+        }
+        for change in &best {
+            self.modifier.redo(change, obj);
+            self.modifier.redo_meaning(change);
+        }
+        let stop = stop.unwrap_or(Stop::Completed);
+        self.observer.stop(&stop);
+        (best, stop)
+    }
+}
+
+impl<T, M, U, C, O> Modifier<T> for ConstrainedOptimizer<M, U, C, O>
+    where M: Modifier<T>, U: Utility<T>, C: Constraint<T>, M::Change: Clone, O: Observer
+{
+    type Change = Vec<M::Change>;
+    fn modify(&mut self, obj: &mut T) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        self.optimize(obj, rng).0
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut T) {
+        for change in change.iter().rev() {
+            self.modifier.undo(change, obj);
+            self.modifier.undo_meaning(change);
+        }
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut T) {
+        for change in change {
+            self.modifier.redo(change, obj);
+            self.modifier.redo_meaning(change);
+        }
+    }
+}
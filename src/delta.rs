@@ -0,0 +1,88 @@
+//! Incremental utility evaluation.
+//!
+//! Computing the full utility after each modification often takes time proportional
+//! to the size of the object, while a change only touches a few parts of it.
+//! `DeltaUtility` computes the difference in utility from the change instead.
+//!
+//! `ModifyOptimizer`, `SimulatedAnnealing` and `TabuSearch` use deltas when evaluating by
+//! `Evaluation::Delta`, falling back to the full utility when no delta is available.
+//! Since deltas are added up, rounding errors of floats may build up during long runs.
+//! `Evaluation::Checked` cross-checks every delta against the full utility while debugging.
+
+use Utility;
+
+/// Implemented by utilities that compute the difference in utility caused by a change.
+pub trait DeltaUtility<T, C>: Utility<T> {
+    /// Computes the difference in utility caused by a change, which has been applied to the object.
+    ///
+    /// Returns `None` to fall back to computing the full utility.
+    fn delta(&self, obj: &T, change: &C) -> Option<f64>;
+}
+
+/// Computes the delta of the change picked from a list of modifiers,
+/// e.g. `Vec<M>`, `Weighted<M>` or `AdaptiveModifier<M>`.
+impl<T, C, U: DeltaUtility<T, C>> DeltaUtility<T, (usize, C)> for U {
+    fn delta(&self, obj: &T, change: &(usize, C)) -> Option<f64> {self.delta(obj, &change.1)}
+}
+
+/// How optimizers evaluate utility after modifying an object.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Evaluation {
+    /// Computes the full utility.
+    Full,
+    /// Adds the delta of the change to the previous utility,
+    /// falling back to the full utility when no delta is available.
+    Delta,
+    /// Like `Delta`, but panics when the result differs from the full utility
+    /// by more than a tolerance.
+    Checked(f64),
+}
+
+/// Computes the delta of a change.
+type DeltaFn<U, T, C> = fn(&U, &T, &C) -> Option<f64>;
+
+/// Evaluates utility after a change.
+pub(crate) struct Evaluator<U, T, C> {
+    delta: Option<DeltaFn<U, T, C>>,
+    tolerance: Option<f64>,
+}
+
+impl<U: Utility<T>, T, C> Evaluator<U, T, C> {
+    /// Computes the full utility.
+    pub fn full() -> Evaluator<U, T, C> {Evaluator {delta: None, tolerance: None}}
+
+    /// Evaluates by a strategy.
+    pub fn new(evaluation: Evaluation) -> Evaluator<U, T, C>
+        where U: DeltaUtility<T, C>
+    {
+        let delta: DeltaFn<U, T, C> = <U as DeltaUtility<T, C>>::delta;
+        match evaluation {
+            Evaluation::Full => Evaluator::full(),
+            Evaluation::Delta => Evaluator {delta: Some(delta), tolerance: None},
+            Evaluation::Checked(tolerance) => {
+                Evaluator {delta: Some(delta), tolerance: Some(tolerance)}
+            }
+        }
+    }
+
+    /// Computes the utility of an object after a change, given the utility before it.
+    pub fn utility(&self, utility: &U, obj: &T, change: &C, previous: f64) -> f64 {
+        let delta = match self.delta {
+            Some(delta) => delta(utility, obj, change),
+            None => None,
+        };
+        match delta {
+            Some(delta) => {
+                let result = previous + delta;
+                if let Some(tolerance) = self.tolerance {
+                    let full = utility.utility(obj);
+                    if (result - full).abs() > tolerance {
+                        panic!("Delta utility {} differs from full utility {}", result, full);
+                    }
+                }
+                result
+            }
+            None => utility.utility(obj),
+        }
+    }
+}
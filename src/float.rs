@@ -0,0 +1,109 @@
+//! Serializes floats that JSON can not represent.
+//!
+//! JSON has no infinity or NaN, which e.g. `serde_json` writes as `null` and fails to read back.
+//! Use `#[serde(with = "float")]` on `f64` fields to write these as strings instead.
+//! This also works on fields of a generic `N: Number`, where integers are written as usual.
+//! Use `float::option` for `Option<f64>` and `float::weights` for `Vec<(f64, T)>`.
+
+use std::f64;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use numeric::Number;
+
+/// A number that is serialized with this module.
+struct Float<N>(N);
+
+impl<N: Number + Serialize> Serialize for Float<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, N: Number + Deserialize<'de>> Deserialize<'de> for Float<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Float<N>, D::Error> {
+        deserialize(deserializer).map(Float)
+    }
+}
+
+/// A finite number or the name of a non-finite one.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Value<N> {
+    Finite(N),
+    NonFinite(String),
+}
+
+/// Returns `true` if the number type can be infinite, since integers saturate instead.
+fn is_float<N: Number>() -> bool {
+    N::from_f64(f64::INFINITY).to_f64().is_infinite()
+}
+
+pub fn serialize<N, S>(value: &N, serializer: S) -> Result<S::Ok, S::Error>
+    where N: Number + Serialize, S: Serializer
+{
+    let x = value.to_f64();
+    if x.is_finite() {
+        value.serialize(serializer)
+    } else if x.is_nan() {
+        serializer.serialize_str("NaN")
+    } else if x > 0.0 {
+        serializer.serialize_str("inf")
+    } else {
+        serializer.serialize_str("-inf")
+    }
+}
+
+pub fn deserialize<'de, N, D>(deserializer: D) -> Result<N, D::Error>
+    where N: Number + Deserialize<'de>, D: Deserializer<'de>
+{
+    // Integers are read directly, since untagged values do not support all integer types.
+    if !is_float::<N>() {return N::deserialize(deserializer)}
+    match Value::deserialize(deserializer)? {
+        Value::Finite(value) => Ok(value),
+        Value::NonFinite(name) => match &*name {
+            "NaN" => Ok(N::from_f64(f64::NAN)),
+            "inf" => Ok(N::from_f64(f64::INFINITY)),
+            "-inf" => Ok(N::from_f64(f64::NEG_INFINITY)),
+            _ => Err(D::Error::custom(format!("expected a number, found `{}`", name))),
+        },
+    }
+}
+
+/// Serializes an optional float.
+pub mod option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Float;
+
+    pub fn serialize<S: Serializer>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.map(Float).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+        where D: Deserializer<'de>
+    {
+        Ok(Option::<Float<f64>>::deserialize(deserializer)?.map(|it| it.0))
+    }
+}
+
+/// Serializes a list of items with float weights.
+pub mod weights {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Float;
+
+    pub fn serialize<T, S>(items: &[(f64, T)], serializer: S) -> Result<S::Ok, S::Error>
+        where T: Serialize, S: Serializer
+    {
+        serializer.collect_seq(items.iter().map(|&(weight, ref it)| (Float(weight), it)))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<(f64, T)>, D::Error>
+        where T: Deserialize<'de>, D: Deserializer<'de>
+    {
+        let items: Vec<(Float<f64>, T)> = Deserialize::deserialize(deserializer)?;
+        Ok(items.into_iter().map(|(weight, it)| (weight.0, it)).collect())
+    }
+}
@@ -0,0 +1,57 @@
+//! Focusing on a part of an object.
+
+use rand::RngCore;
+
+use {Modifier, Utility};
+
+/// Lifts a utility or modifier of a part into one of the whole object.
+///
+/// The accessor returns a reference to the part, e.g. a field of a struct:
+///
+/// - `Utility<S>` is implemented when the accessor is `Fn(&S) -> &A` and `X: Utility<A>`
+/// - `Modifier<S>` is implemented when the accessor is `Fn(&mut S) -> &mut A`
+///   and `X: Modifier<A>`
+///
+/// Changes are tagged with the id of the part,
+/// such that the change in meaning is only forwarded when the change was made to the same part.
+/// Give each part a different id, and the same id to modifiers of the same part.
+/// Use functions as accessors, e.g. `field`,
+/// since closures do not infer that the returned reference borrows from the argument.
+#[derive(Copy, Clone, Debug)]
+pub struct Focus<X, F>(pub X, pub F, pub usize);
+
+impl<X, F> Focus<X, F> {
+    /// Creates a new focus of a utility or modifier on the part with an id.
+    pub fn new(inner: X, accessor: F, part: usize) -> Focus<X, F> {Focus(inner, accessor, part)}
+}
+
+impl<S, A, X, F> Utility<S> for Focus<X, F>
+    where X: Utility<A>, F: Fn(&S) -> &A
+{
+    fn utility(&self, obj: &S) -> f64 {self.0.utility((self.1)(obj))}
+}
+
+impl<S, A, X, F> Modifier<S> for Focus<X, F>
+    where X: Modifier<A>, F: Fn(&mut S) -> &mut A
+{
+    type Change = (usize, X::Change);
+    fn modify(&mut self, obj: &mut S) -> Self::Change {
+        (self.2, self.0.modify((self.1)(obj)))
+    }
+    fn modify_with(&mut self, obj: &mut S, rng: &mut dyn RngCore) -> Self::Change {
+        (self.2, self.0.modify_with((self.1)(obj), rng))
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut S) {
+        self.0.undo(&change.1, (self.1)(obj))
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut S) {
+        self.0.redo(&change.1, (self.1)(obj))
+    }
+    fn undo_meaning(&mut self, change: &Self::Change) {
+        if change.0 == self.2 {self.0.undo_meaning(&change.1)}
+    }
+    fn redo_meaning(&mut self, change: &Self::Change) {
+        if change.0 == self.2 {self.0.redo_meaning(&change.1)}
+    }
+    fn feedback(&mut self, change: &Self::Change, delta: f64) {self.0.feedback(&change.1, delta)}
+}
@@ -0,0 +1,223 @@
+//! Genetic algorithm.
+
+use std::cmp::Ordering;
+
+use rand::{Rng, RngCore};
+
+use {Generator, Modifier, Observer, Progress, Stop, Termination, Utility};
+
+/// Implemented by objects that recombine two objects into a new one.
+pub trait Crossover<T> {
+    /// Combines two parents into a child.
+    ///
+    /// This might be indeterministic.
+    fn crossover(&mut self, a: &T, b: &T) -> T;
+    /// Combines two parents into a child using a random number generator.
+    ///
+    /// Crossovers that make random choices should override this method,
+    /// such that the same seed produces the same child.
+    /// The default implementation ignores the random number generator.
+    fn crossover_with(&mut self, a: &T, b: &T, _rng: &mut dyn RngCore) -> T {
+        self.crossover(a, b)
+    }
+}
+
+impl<T, C: Crossover<T>> Crossover<T> for Vec<C> {
+    fn crossover(&mut self, a: &T, b: &T) -> T {
+        self.crossover_with(a, b, &mut ::rand::thread_rng())
+    }
+    fn crossover_with(&mut self, a: &T, b: &T, rng: &mut dyn RngCore) -> T {
+        let index = rng.gen_range(0, self.len());
+        self[index].crossover_with(a, b, rng)
+    }
+}
+
+/// Selection strategy for picking parents.
+#[derive(Copy, Clone, Debug)]
+pub enum Selection {
+    /// Picks the best among a number of random individuals.
+    Tournament(usize),
+    /// Picks with probability proportional to utility above the worst.
+    Roulette,
+    /// Picks with probability proportional to rank, where the worst has rank 1.
+    Rank,
+    /// Picks uniformly among the best fraction of the population.
+    Truncation(f64),
+}
+
+impl Selection {
+    /// Selects an index given utilities sorted from best to worst.
+    pub fn select(&self, utilities: &[f64], rng: &mut dyn RngCore) -> usize {
+        let n = utilities.len();
+        match *self {
+            Selection::Tournament(size) => {
+                let mut best = rng.gen_range(0, n);
+                for _ in 1..size {
+                    let i = rng.gen_range(0, n);
+                    if utilities[best] < utilities[i] {best = i}
+                }
+                best
+            }
+            Selection::Roulette => {
+                let worst = utilities[n - 1];
+                let total: f64 = utilities.iter().map(|u| u - worst).sum();
+                if total <= 0.0 || total.is_nan() {return rng.gen_range(0, n)}
+                let mut r = rng.gen::<f64>() * total;
+                for (i, u) in utilities.iter().enumerate() {
+                    r -= u - worst;
+                    if r < 0.0 {return i}
+                }
+                n - 1
+            }
+            Selection::Rank => {
+                let total = n * (n + 1) / 2;
+                let mut r = rng.gen_range(0, total);
+                for i in 0..n {
+                    let rank = n - i;
+                    if r < rank {return i}
+                    r -= rank;
+                }
+                n - 1
+            }
+            Selection::Truncation(fraction) => {
+                let m = ((n as f64 * fraction).ceil() as usize).max(1).min(n);
+                rng.gen_range(0, m)
+            }
+        }
+    }
+}
+
+/// Evolves a population of objects by selection, crossover and mutation.
+///
+/// The initial population is generated by the generator.
+/// A modifier is used as mutation, where the change is discarded.
+///
+/// Generates the best object found after evolving the population.
+/// This panics if the population size is zero.
+pub struct GeneticOptimizer<G, C, M, U, O = ()> {
+    /// Generates the initial population.
+    pub generator: G,
+    /// Recombines parents.
+    pub crossover: C,
+    /// Mutates children.
+    pub mutation: M,
+    /// The measured utility.
+    pub utility: U,
+    /// The selection strategy for parents.
+    pub selection: Selection,
+    /// The size of the population.
+    pub population: usize,
+    /// The number of best individuals that survive to the next generation unchanged.
+    pub elitism: usize,
+    /// The probability of mutating a child.
+    pub mutation_rate: f64,
+    /// The number of generations.
+    pub generations: usize,
+    /// The criterion for stopping before all generations are completed.
+    pub termination: Termination,
+    /// Observes the optimization.
+    ///
+    /// Each generation is reported as a try and each child as a change.
+    pub observer: O,
+}
+
+impl<T, G, C, M, U, O> GeneticOptimizer<G, C, M, U, O>
+    where T: Clone, G: Generator<Output = T>, C: Crossover<T>, M: Modifier<T>, U: Utility<T>,
+          O: Observer
+{
+    /// Evolves a population and returns it with utilities, sorted from best to worst,
+    /// together with the reason for stopping.
+    pub fn evolve(&mut self, rng: &mut dyn RngCore) -> (Vec<(T, f64)>, Stop) {
+        self.evolve_in(rng, 1, |utility, objs| {
+            objs.iter().map(|it| utility.utility(it)).collect()
+        })
+    }
+
+    /// Evolves a population, evaluating up to `batch` children at once.
+    pub(crate) fn evolve_in<F>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        batch: usize,
+        evaluate: F
+    ) -> (Vec<(T, f64)>, Stop)
+        where F: Fn(&U, &[T]) -> Vec<f64>
+    {
+        let objs: Vec<T> = (0..self.population)
+            .map(|_| self.generator.generate_with(rng)).collect();
+        let utilities = evaluate(&self.utility, &objs);
+        let mut population: Vec<(T, f64)> = objs.into_iter().zip(utilities).collect();
+        sort(&mut population);
+        if population.is_empty() {return (population, Stop::Completed)}
+        self.observer.start(population[0].1);
+        for it in &population[1..] {self.observer.change(it.1)}
+        let mut progress = Progress::new(population[0].1);
+        progress.evaluations = population.len();
+        let mut stop = None;
+        for i in 0..self.generations {
+            self.observer.next_try(i);
+            let utilities: Vec<f64> = population.iter().map(|it| it.1).collect();
+            let mut next: Vec<(T, f64)> = population.iter()
+                .take(self.elitism).cloned().collect();
+            while next.len() < self.population {
+                let n = batch.max(1).min(self.population - next.len());
+                let mut children = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let a = self.selection.select(&utilities, rng);
+                    let b = self.selection.select(&utilities, rng);
+                    let mut child = self.crossover
+                        .crossover_with(&population[a].0, &population[b].0, rng);
+                    if rng.gen::<f64>() < self.mutation_rate {
+                        self.mutation.modify_with(&mut child, rng);
+                    }
+                    children.push(child);
+                }
+                let children_utilities = evaluate(&self.utility, &children);
+                for (child, utility) in children.into_iter().zip(children_utilities) {
+                    next.push((child, utility));
+                    self.observer.change(utility);
+                    if progress.best_utility < utility {self.observer.improve(utility)}
+                    progress.evaluate(utility);
+                    stop = self.termination.check(&progress);
+                    if stop.is_some() {break}
+                }
+                if stop.is_some() {break}
+            }
+            if let Some(stop) = stop {
+                // Fill up with the survivors of the previous generation.
+                let elitism = self.elitism.min(population.len());
+                next.extend(population.drain(elitism..));
+                sort(&mut next);
+                next.truncate(self.population);
+                self.observer.stop(&stop);
+                return (next, stop);
+            }
+            population = next;
+            sort(&mut population);
+        }
+        self.observer.stop(&Stop::Completed);
+        (population, Stop::Completed)
+    }
+}
+
+impl<T, G, C, M, U, O> Generator for GeneticOptimizer<G, C, M, U, O>
+    where T: Clone, G: Generator<Output = T>, C: Crossover<T>, M: Modifier<T>, U: Utility<T>,
+          O: Observer
+{
+    type Output = T;
+    fn generate(&mut self) -> T {
+        self.generate_with(&mut ::rand::thread_rng())
+    }
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> T {
+        self.evolve(rng).0.swap_remove(0).0
+    }
+}
+
+fn sort<T>(population: &mut [(T, f64)]) {
+    population.sort_by(|a, b| compare(b.1, a.1));
+}
+
+/// Compares utilities by a total order, where NaN is worse than any other utility.
+pub(crate) fn compare(a: f64, b: f64) -> Ordering {
+    fn key(x: f64) -> f64 {if x.is_nan() {f64::NEG_INFINITY} else {x}}
+    key(a).total_cmp(&key(b))
+}
@@ -0,0 +1,175 @@
+//! Implementations of traits for standard types.
+
+use rand::{Rng, RngCore};
+
+use {Generator, Modifier, Utility};
+
+impl<T, U: Utility<T> + ?Sized> Utility<T> for Box<U> {
+    fn utility(&self, obj: &T) -> f64 {(**self).utility(obj)}
+}
+
+impl<T, U: Utility<T> + ?Sized> Utility<T> for &U {
+    fn utility(&self, obj: &T) -> f64 {(**self).utility(obj)}
+}
+
+/// Zero utility when there is no sub-utility.
+impl<T, U: Utility<T>> Utility<T> for Option<U> {
+    fn utility(&self, obj: &T) -> f64 {
+        match *self {
+            Some(ref u) => u.utility(obj),
+            None => 0.0,
+        }
+    }
+}
+
+/// Sums up utility from multiple sub-terms.
+impl<T, U: Utility<T>> Utility<T> for [U] {
+    fn utility(&self, obj: &T) -> f64 {
+        self.iter().map(|it| it.utility(obj)).sum()
+    }
+}
+
+/// Sums up utility from multiple sub-terms.
+impl<T, U: Utility<T>, const N: usize> Utility<T> for [U; N] {
+    fn utility(&self, obj: &T) -> f64 {
+        self.iter().map(|it| it.utility(obj)).sum()
+    }
+}
+
+macro_rules! tuple_utility {
+    ($($u:ident: $i:tt),+) => {
+        /// Sums up utility from sub-terms of different types.
+        impl<T, $($u: Utility<T>),+> Utility<T> for ($($u,)+) {
+            fn utility(&self, obj: &T) -> f64 {
+                0.0 $(+ self.$i.utility(obj))+
+            }
+        }
+    }
+}
+
+tuple_utility!(A: 0);
+tuple_utility!(A: 0, B: 1);
+tuple_utility!(A: 0, B: 1, C: 2);
+tuple_utility!(A: 0, B: 1, C: 2, D: 3);
+tuple_utility!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_utility!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple_utility!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple_utility!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+
+impl<G: Generator + ?Sized> Generator for Box<G> {
+    type Output = G::Output;
+    fn generate(&mut self) -> Self::Output {(**self).generate()}
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Self::Output {
+        (**self).generate_with(rng)
+    }
+}
+
+impl<G: Generator + ?Sized> Generator for &mut G {
+    type Output = G::Output;
+    fn generate(&mut self) -> Self::Output {(**self).generate()}
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Self::Output {
+        (**self).generate_with(rng)
+    }
+}
+
+/// Picks a random generator to generate the object.
+impl<G: Generator, const N: usize> Generator for [G; N] {
+    type Output = G::Output;
+    fn generate(&mut self) -> Self::Output {
+        self.generate_with(&mut ::rand::thread_rng())
+    }
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Self::Output {
+        let index = rng.gen_range(0, N);
+        self[index].generate_with(rng)
+    }
+}
+
+macro_rules! forward_modifier {
+    () => {
+        type Change = M::Change;
+        fn modify(&mut self, obj: &mut T) -> Self::Change {(**self).modify(obj)}
+        fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+            (**self).modify_with(obj, rng)
+        }
+        fn undo(&mut self, change: &Self::Change, obj: &mut T) {(**self).undo(change, obj)}
+        fn redo(&mut self, change: &Self::Change, obj: &mut T) {(**self).redo(change, obj)}
+        fn undo_meaning(&mut self, change: &Self::Change) {(**self).undo_meaning(change)}
+        fn redo_meaning(&mut self, change: &Self::Change) {(**self).redo_meaning(change)}
+        fn feedback(&mut self, change: &Self::Change, delta: f64) {
+            (**self).feedback(change, delta)
+        }
+    }
+}
+
+impl<T, M: Modifier<T> + ?Sized> Modifier<T> for Box<M> {
+    forward_modifier!{}
+}
+
+impl<T, M: Modifier<T> + ?Sized> Modifier<T> for &mut M {
+    forward_modifier!{}
+}
+
+/// Picks a random modifier to modify the object.
+impl<T, M: Modifier<T>, const N: usize> Modifier<T> for [M; N] {
+    type Change = (usize, M::Change);
+    fn modify(&mut self, obj: &mut T) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        let index = rng.gen_range(0, N);
+        (index, self[index].modify_with(obj, rng))
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut T) {
+        self[change.0].undo(&change.1, obj)
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut T) {
+        self[change.0].redo(&change.1, obj)
+    }
+    fn undo_meaning(&mut self, change: &Self::Change) {
+        for it in self {it.undo_meaning(&change.1)}
+    }
+    fn redo_meaning(&mut self, change: &Self::Change) {
+        for it in self {it.redo_meaning(&change.1)}
+    }
+    fn feedback(&mut self, change: &Self::Change, delta: f64) {
+        self[change.0].feedback(&change.1, delta)
+    }
+}
+
+/// Computes utility using a closure.
+#[derive(Copy, Clone)]
+pub struct UtilityFn<F>(pub F);
+
+impl<T, F: Fn(&T) -> f64> Utility<T> for UtilityFn<F> {
+    fn utility(&self, obj: &T) -> f64 {(self.0)(obj)}
+}
+
+/// Generates objects using a closure.
+#[derive(Copy, Clone)]
+pub struct GeneratorFn<F>(pub F);
+
+impl<T, F: FnMut() -> T> Generator for GeneratorFn<F> {
+    type Output = T;
+    fn generate(&mut self) -> T {(self.0)()}
+}
+
+/// Modifies objects using a closure that computes a new value from the old one.
+///
+/// The change stores the old and new value.
+#[derive(Copy, Clone)]
+pub struct ModifierFn<F>(pub F);
+
+impl<T: Clone, F: FnMut(&T) -> T> Modifier<T> for ModifierFn<F> {
+    type Change = (T, T);
+    fn modify(&mut self, obj: &mut T) -> Self::Change {
+        let new = (self.0)(obj);
+        let old = ::std::mem::replace(obj, new.clone());
+        (old, new)
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut T) {
+        *obj = change.0.clone()
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut T) {
+        *obj = change.1.clone()
+    }
+}
@@ -0,0 +1,92 @@
+//! Journals of changes.
+//!
+//! This module requires the `serde` feature.
+//!
+//! A `Journal` records the changes made to an object, e.g. by an optimizer,
+//! such that they can be saved and later replayed with `redo` on a fresh object.
+//! Journals are stored as JSON lines, one change per line,
+//! which keeps them readable as an audit trail.
+//!
+//! The changes of the standard modifiers are serializable,
+//! including nested changes of `Vec<M>`, i.e. `(usize, M::Change)`,
+//! and of `ModifyOptimizer`, i.e. `Vec<M::Change>`.
+//! Changes of `#[derive(Modifier)]` are serializable when marked with `#[utility(serde)]`.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use Modifier;
+
+/// Records changes made to an object.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Journal<C> {
+    /// The changes in the order they were made.
+    pub changes: Vec<C>,
+}
+
+impl<C> Journal<C> {
+    /// Creates an empty journal.
+    pub fn new() -> Journal<C> {Journal {changes: vec![]}}
+
+    /// Records a change.
+    pub fn record(&mut self, change: C) {self.changes.push(change)}
+
+    /// Redoes the changes on an object in order, updating the meaning of the modifier.
+    ///
+    /// The object and modifier should be in the same state as when the changes were made.
+    pub fn replay<T, M>(&self, modifier: &mut M, obj: &mut T)
+        where M: Modifier<T, Change = C>
+    {
+        for change in &self.changes {
+            modifier.redo(change, obj);
+            modifier.redo_meaning(change);
+        }
+    }
+}
+
+impl<C> Default for Journal<C> {
+    fn default() -> Journal<C> {Journal::new()}
+}
+
+impl<C> From<Vec<C>> for Journal<C> {
+    fn from(changes: Vec<C>) -> Journal<C> {Journal {changes}}
+}
+
+impl<C: Serialize> Journal<C> {
+    /// Writes the journal as JSON lines.
+    pub fn write<W: Write>(&self, mut w: W) -> io::Result<()> {
+        for change in &self.changes {
+            serde_json::to_writer(&mut w, change)?;
+            w.write_all(b"\n")?;
+        }
+        w.flush()
+    }
+
+    /// Saves the journal to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<C: DeserializeOwned> Journal<C> {
+    /// Reads a journal from JSON lines, skipping empty lines.
+    pub fn read<R: Read>(r: R) -> io::Result<Journal<C>> {
+        let mut changes = vec![];
+        for line in BufReader::new(r).lines() {
+            let line = line?;
+            if line.trim().is_empty() {continue}
+            changes.push(serde_json::from_str(&line)?);
+        }
+        Ok(Journal {changes})
+    }
+
+    /// Loads a journal from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Journal<C>> {
+        Journal::read(File::open(path)?)
+    }
+}
@@ -40,9 +40,80 @@
 //! - `Vec<T: Generator>` picks a random generator to generate the object
 //! - `Vec<T: Modifier>` picks a random modifier to modify the object
 //!
+//! Utilities can be combined with methods such as `scale`, `min`, `max`, `clamp` and `sigmoid`,
+//! e.g. `u.scale(2.0).max(v)`.
+//!
 //! It is common to use `enum` instead of `struct` to combine variants with `Vec<T>`.
+//! Tuples of utilities sum up terms of different types.
+//! Closures can be used through `UtilityFn`, `GeneratorFn` and `ModifierFn`.
+//!
+//! `ListModifier` inserts, removes, swaps, moves, reverses or replaces items of a `Vec<T>`,
+//! optionally restricted to a range that follows the items when other list modifiers act.
+//!
+//! `NumberModifier` steps, perturbs, resamples or flips bits of integers and floats,
+//! optionally adapting its step size by the 1/5th success rule.
+//! `PermutationModifier` reorders items with 2-opt, 3-opt, or-opt, swap, insertion or scramble,
+//! using compact changes. The `tsplib` module reads traveling salesman problems.
+//!
+//! `Focus` lifts a utility or modifier of a part, e.g. a field, into one of the whole object.
+//! `Cached` remembers the utility of recently measured objects by a fingerprint,
+//! e.g. `u.cached(cache::hashed, 1000)`, evicting the least recently used ones.
+//!
+//! To bias the random choice, use `Weighted<T>` which picks proportionally to weights.
+//! `AdaptiveModifier` learns which modifier tends to improve utility from optimizer feedback.
 //!
 //! Modification requires `undo` and `redo` for backtracking and replication.
+//! With the `testing` feature, `testing::check` verifies these on random sequences of changes,
+//! and `testing::check_meaning` verifies that modifiers sharing an object keep their meaning.
+//! With the `serde` feature, changes are serializable,
+//! and `journal::Journal` saves changes and replays them with `redo` on a fresh object.
+//!
+//! With the `derive` feature, `#[derive(Utility)]` sums up the utility of fields,
+//! using `#[utility(weight = ...)]` to weight a field or variant.
+//! `#[derive(Generator)]` and `#[derive(Modifier)]` dispatch over enum variants,
+//! where the latter generates a `<Name>Change` enum for the changes.
+//! On the type, `#[utility(crate = "::up")]` refers to a renamed library
+//! and `#[utility(serde)]` makes the changes of a derived modifier serializable.
+//!
+//! ### Optimizers
+//!
+//! - `ModifyOptimizer` tries sequences of modifications and keeps the best one,
+//!   created with `ModifyOptimizer::new(modifier, utility, tries, depth)`
+//! - `ConstrainedOptimizer` works like `ModifyOptimizer` subject to a hard `Constraint`
+//! - `SimulatedAnnealing` accepts worse modifications with a temperature-controlled probability
+//! - `TabuSearch` moves to the best neighbor that does not revisit recent states
+//! - `GeneticOptimizer` evolves a population using a `Crossover` and a modifier as mutation
+//! - `Nsga2` evolves a `ParetoArchive` of trade-offs between objectives of a `MultiUtility`
+//!
+//! Every optimizer accepts a `Termination` criterion for stopping early,
+//! e.g. on a time limit, a budget of utility evaluations, stagnation or a target utility.
+//! The methods `optimize` and `evolve` return the result together with the `Stop` reason.
+//! A `DeltaUtility` computes the difference in utility from a change instead of the full utility,
+//! which `optimize_by` uses with `Evaluation::Delta`, or cross-checks with `Evaluation::Checked`.
+//! `ModifyOptimizer::steps` yields after each try with the best utility and changes so far,
+//! such that optimization can be stopped early or interleaved with other work.
+//! `Steps::checkpoint` saves the state between tries and `ModifyOptimizer::resume` continues it,
+//! which with the `serde` feature survives a process restart by saving the `Checkpoint` to a file.
+//! With the `parallel` feature, `ModifyOptimizer::optimize_parallel` runs tries across threads
+//! and `evolve_parallel` evaluates the population of `GeneticOptimizer` and `Nsga2` in parallel.
+//! `parallel::Parallel` sums up a `Vec` of expensive utilities in parallel.
+//! `Dominance` measures a `MultiUtility` by Pareto dominance against reference points,
+//! e.g. the members of a `ParetoArchive`, for use with single-objective optimizers.
+//! An `Observer` can be attached to receive events for each try, change, improvement and backtrack.
+//!
+//! ### Reproducible randomness
+//!
+//! Generators and modifiers that make random choices take a random number generator
+//! through `Generator::generate_with` and `Modifier::modify_with`.
+//! The methods `generate` and `modify` use the thread local random number generator.
+//!
+//! By passing a seeded random number generator, e.g. `rand::rngs::StdRng`,
+//! a fixed seed yields identical optimization runs:
+//!
+//! ```ignore
+//! let mut rng = StdRng::seed_from_u64(0);
+//! let change = optimizer.modify_with(&mut obj, &mut rng);
+//! ```
 //!
 //! ### Utility Epistomology
 //!
@@ -74,11 +145,107 @@
 //! [Zen Rationality](https://github.com/advancedresearch/path_semantics/blob/master/papers-wip/zen-rationality.pdf).
 
 extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "derive")]
+extern crate utility_programming_derive;
+
+use rand::Rng;
+use rand::rngs::ThreadRng;
+
+use combinators::{Clamp, Log, Max, Min, Neg, Pow, Product, Scaled, Sigmoid, Threshold};
+
+pub use rand::RngCore;
+#[cfg(feature = "derive")]
+pub use utility_programming_derive::{Generator, Modifier, Utility};
+pub use adaptive::AdaptiveModifier;
+pub use annealing::SimulatedAnnealing;
+pub use cache::Cached;
+pub use constraint::{ConstrainedOptimizer, Constraint};
+pub use delta::{DeltaUtility, Evaluation};
+pub use focus::Focus;
+pub use genetic::{Crossover, GeneticOptimizer};
+pub use impls::{GeneratorFn, ModifierFn, UtilityFn};
+pub use list::ListModifier;
+pub use numeric::NumberModifier;
+pub use observer::Observer;
+pub use pareto::{Dominance, MultiUtility, Nsga2, ParetoArchive};
+pub use permutation::PermutationModifier;
+pub use steps::{Checkpoint, Steps};
+pub use tabu::TabuSearch;
+pub use termination::{Progress, Stop, Termination};
+pub use tsplib::Tsp;
+pub use weighted::Weighted;
+
+pub mod adaptive;
+pub mod annealing;
+pub mod cache;
+pub mod combinators;
+pub mod constraint;
+pub mod delta;
+pub mod focus;
+pub mod genetic;
+#[cfg(feature = "serde")]
+pub mod journal;
+pub mod list;
+pub mod numeric;
+pub mod observer;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod pareto;
+pub mod permutation;
+pub mod steps;
+pub mod tabu;
+pub mod termination;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod tsplib;
+pub mod weighted;
+
+#[cfg(feature = "serde")]
+mod float;
+mod impls;
 
 /// Implemented by objects that measure utility of an object.
 pub trait Utility<T> {
     /// Computes the utility of an object.
     fn utility(&self, obj: &T) -> f64;
+
+    /// Multiplies utility by a factor.
+    fn scale(self, factor: f64) -> Scaled<Self> where Self: Sized {Scaled(self, factor)}
+    /// Negates utility.
+    fn neg(self) -> Neg<Self> where Self: Sized {Neg(self)}
+    /// Picks the least of this and another utility.
+    fn min<V: Utility<T>>(self, other: V) -> Min<Self, V> where Self: Sized {Min(self, other)}
+    /// Picks the greatest of this and another utility.
+    fn max<V: Utility<T>>(self, other: V) -> Max<Self, V> where Self: Sized {Max(self, other)}
+    /// Multiplies this with another utility.
+    fn product<V: Utility<T>>(self, other: V) -> Product<Self, V> where Self: Sized {
+        Product(self, other)
+    }
+    /// Returns `1.0` when utility reaches a threshold, `0.0` otherwise.
+    fn threshold(self, threshold: f64) -> Threshold<Self> where Self: Sized {
+        Threshold(self, threshold)
+    }
+    /// Restricts utility to a range `[min, max]`.
+    fn clamp(self, min: f64, max: f64) -> Clamp<Self> where Self: Sized {Clamp(self, min, max)}
+    /// Takes the natural logarithm of utility.
+    fn log(self) -> Log<Self> where Self: Sized {Log(self)}
+    /// Raises utility to a power.
+    fn pow(self, exponent: f64) -> Pow<Self> where Self: Sized {Pow(self, exponent)}
+    /// Maps utility smoothly to the range `(0, 1)`.
+    fn sigmoid(self) -> Sigmoid<Self> where Self: Sized {Sigmoid(self)}
+    /// Caches utility by a fingerprint of objects, keeping up to `capacity` utilities.
+    fn cached<F: Fn(&T) -> u64>(self, fingerprint: F, capacity: usize) -> Cached<Self, F>
+        where Self: Sized
+    {
+        Cached::new(self, fingerprint, capacity)
+    }
 }
 
 /// Sums up utility from multiple sub-terms.
@@ -96,13 +263,24 @@ pub trait Generator {
     ///
     /// This might be indeterministic.
     fn generate(&mut self) -> Self::Output;
+    /// Generate a new object using a random number generator.
+    ///
+    /// Generators that make random choices should override this method,
+    /// such that the same seed generates the same object.
+    /// The default implementation ignores the random number generator.
+    fn generate_with(&mut self, _rng: &mut dyn RngCore) -> Self::Output {
+        self.generate()
+    }
 }
 
 impl<T: Generator> Generator for Vec<T> {
     type Output = T::Output;
     fn generate(&mut self) -> Self::Output {
-        let index = rand::random::<usize>() % self.len();
-        self[index].generate()
+        self.generate_with(&mut rand::thread_rng())
+    }
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Self::Output {
+        let index = rng.gen_range(0, self.len());
+        self[index].generate_with(rng)
     }
 }
 
@@ -133,6 +311,14 @@ pub trait Modifier<T> {
     /// This might be indeterministic.
     /// Use `redo_meaning` for applying change in meaning of modifier.
     fn modify(&mut self, obj: &mut T) -> Self::Change;
+    /// Modify an object using a random number generator and return the change.
+    ///
+    /// Modifiers that make random choices should override this method,
+    /// such that the same seed produces the same change.
+    /// The default implementation ignores the random number generator.
+    fn modify_with(&mut self, obj: &mut T, _rng: &mut dyn RngCore) -> Self::Change {
+        self.modify(obj)
+    }
     /// Undo change made to an object.
     ///
     /// Required to be deterministic.
@@ -149,13 +335,21 @@ pub trait Modifier<T> {
     ///
     /// This is called after modification by any modifier used in same context.
     fn redo_meaning(&mut self, _change: &Self::Change) {}
+    /// Receives feedback about the change in utility caused by a change.
+    ///
+    /// This is called by optimizers after measuring the utility of a modification.
+    /// A positive `delta` means the modification increased utility.
+    fn feedback(&mut self, _change: &Self::Change, _delta: f64) {}
 }
 
 impl<T, U: Modifier<T>> Modifier<T> for Vec<U> {
     type Change = (usize, U::Change);
     fn modify(&mut self, obj: &mut T) -> Self::Change {
-        let index = rand::random::<usize>() % self.len();
-        (index, self[index].modify(obj))
+        self.modify_with(obj, &mut rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        let index = rng.gen_range(0, self.len());
+        (index, self[index].modify_with(obj, rng))
     }
     fn undo(&mut self, change: &Self::Change, obj: &mut T) {
         self[change.0].undo(&change.1, obj)
@@ -167,12 +361,16 @@ impl<T, U: Modifier<T>> Modifier<T> for Vec<U> {
         for it in self {it.undo_meaning(&change.1)}
     }
     fn redo_meaning(&mut self, change: &Self::Change) {
-        for it in self {it.undo_meaning(&change.1)}
+        for it in self {it.redo_meaning(&change.1)}
+    }
+    fn feedback(&mut self, change: &Self::Change, delta: f64) {
+        self[change.0].feedback(&change.1, delta)
     }
 }
 
 /// Modifies an object using a modifier by maximizing utility.
-pub struct ModifyOptimizer<M, U> {
+#[derive(Clone)]
+pub struct ModifyOptimizer<M, U, O = ()> {
     /// The modifier to modify the object.
     pub modifier: M,
     /// The measured utility.
@@ -181,37 +379,96 @@ pub struct ModifyOptimizer<M, U> {
     pub tries: usize,
     /// The number of repeated modifications before backtracking.
     pub depth: usize,
+    /// The criterion for stopping before all tries are completed.
+    pub termination: Termination,
+    /// Observes the optimization.
+    pub observer: O,
+}
+
+impl<M, U> ModifyOptimizer<M, U> {
+    /// Creates a new optimizer that runs all tries without observing them.
+    ///
+    /// Set `termination` or `observer` afterwards to customize it.
+    pub fn new(modifier: M, utility: U, tries: usize, depth: usize) -> ModifyOptimizer<M, U> {
+        ModifyOptimizer {
+            modifier,
+            utility,
+            tries,
+            depth,
+            termination: Termination::Never,
+            observer: (),
+        }
+    }
+}
+
+impl<M, U, O: Observer> ModifyOptimizer<M, U, O> {
+    /// Modifies an object and returns the change with the reason for stopping.
+    pub fn optimize<T>(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: Utility<T>, M::Change: Clone
+    {
+        let mut steps = self.steps_with(obj, rng);
+        for _ in &mut steps {}
+        steps.finish()
+    }
+
+    /// Modifies an object, evaluating utility by a strategy,
+    /// and returns the change with the reason for stopping.
+    pub fn optimize_by<T>(
+        &mut self,
+        obj: &mut T,
+        rng: &mut dyn RngCore,
+        evaluation: Evaluation
+    ) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: DeltaUtility<T, M::Change>, M::Change: Clone
+    {
+        let mut steps = self.steps_with(obj, rng).evaluate_by(evaluation);
+        for _ in &mut steps {}
+        steps.finish()
+    }
+
+    /// Steps through the tries of optimizing an object.
+    ///
+    /// This uses the thread local random number generator.
+    pub fn steps<'a, T>(&'a mut self, obj: &'a mut T) -> Steps<'a, T, M, U, O, ThreadRng>
+        where M: Modifier<T>, U: Utility<T>
+    {
+        self.steps_with(obj, rand::thread_rng())
+    }
+
+    /// Steps through the tries of optimizing an object, using a random number generator.
+    pub fn steps_with<'a, T, R: RngCore>(
+        &'a mut self,
+        obj: &'a mut T,
+        rng: R
+    ) -> Steps<'a, T, M, U, O, R>
+        where M: Modifier<T>, U: Utility<T>
+    {
+        Steps::new(self, obj, rng)
+    }
+
+    /// Resumes stepping through the tries of optimizing an object from a checkpoint.
+    ///
+    /// The object must be the initial object of the checkpointed optimization.
+    pub fn resume<'a, T, R: RngCore>(
+        &'a mut self,
+        obj: &'a mut T,
+        checkpoint: Checkpoint<M::Change, M, R>
+    ) -> Steps<'a, T, M, U, O, R>
+        where M: Modifier<T>, U: Utility<T>
+    {
+        Steps::resume(self, obj, checkpoint)
+    }
 }
 
-impl<T, M, U> Modifier<T> for ModifyOptimizer<M, U>
-    where M: Modifier<T>, U: Utility<T>, M::Change: Clone
+impl<T, M, U, O> Modifier<T> for ModifyOptimizer<M, U, O>
+    where M: Modifier<T>, U: Utility<T>, M::Change: Clone, O: Observer
 {
     type Change = Vec<M::Change>;
     fn modify(&mut self, obj: &mut T) -> Self::Change {
-        let mut best = vec![];
-        let mut best_utility: f64 = self.utility.utility(obj);
-        let mut stack = vec![];
-        for _ in 0..self.tries {
-            for _ in 0..self.depth {
-                let change = self.modifier.modify(obj);
-                self.modifier.redo_meaning(&change);
-                stack.push(change);
-                let utility = self.utility.utility(obj);
-                if best_utility < utility {
-                    best = stack.clone();
-                    best_utility = utility;
-                }
-            }
-            while let Some(ref action) = stack.pop() {
-                self.modifier.undo(action, obj);
-                self.modifier.undo_meaning(&action);
-            }
-        }
-        for i in 0..best.len() {
-            self.modifier.redo(&best[i], obj);
-            self.modifier.redo_meaning(&best[i]);
-        }
-        best
+        self.modify_with(obj, &mut rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        self.optimize(obj, rng).0
     }
     fn undo(&mut self, change: &Self::Change, obj: &mut T) {
         for i in (0..change.len()).rev() {
@@ -220,9 +477,9 @@ impl<T, M, U> Modifier<T> for ModifyOptimizer<M, U>
         }
     }
     fn redo(&mut self, change: &Self::Change, obj: &mut T) {
-        for i in 0..change.len() {
-            self.modifier.redo(&change[i], obj);
-            self.modifier.redo_meaning(&change[i]);
+        for change in change {
+            self.modifier.redo(change, obj);
+            self.modifier.redo_meaning(change);
         }
     }
 }
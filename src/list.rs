@@ -0,0 +1,294 @@
+//! Modifiers for lists.
+//!
+//! List modifiers share the change type `ListChange<T>`,
+//! such that they can be combined with `Vec<ListModifier<G>>` or `Weighted<ListModifier<G>>`.
+//! Each modifier can be restricted to a range of the list,
+//! which moves and grows or shrinks when other list modifiers insert or remove items.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use rand::{Rng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use {Generator, Modifier};
+
+/// Stores a change made to a list.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ListChange<T> {
+    /// The list was not changed, because there were too few items.
+    Unchanged,
+    /// Inserted a value at an index.
+    Insert {
+        /// The index of the inserted value.
+        index: usize,
+        /// The inserted value.
+        value: T,
+    },
+    /// Removed a value at an index.
+    Remove {
+        /// The index of the removed value.
+        index: usize,
+        /// The removed value.
+        value: T,
+    },
+    /// Swapped two items.
+    Swap(usize, usize),
+    /// Moved an item by removing it and inserting it.
+    Move {
+        /// The index of the item before moving.
+        from: usize,
+        /// The index of the item after moving.
+        to: usize,
+    },
+    /// Reversed the items in a range `[start, end)`.
+    Reverse(usize, usize),
+    /// Replaced the value at an index.
+    Replace {
+        /// The index of the replaced value.
+        index: usize,
+        /// The value before replacing.
+        old: T,
+        /// The value after replacing.
+        new: T,
+    },
+}
+
+impl<T> ListChange<T> {
+    /// Returns `true` if the change inserts or removes items.
+    pub fn is_structural(&self) -> bool {
+        matches!(*self, ListChange::Insert {..} | ListChange::Remove {..} | ListChange::Move {..})
+    }
+
+    /// Computes the range covering the same items after the change.
+    ///
+    /// Items inserted strictly inside the range are included in it,
+    /// while items inserted at its boundaries are not.
+    /// An item moved within the range or its boundaries stays in the range,
+    /// and a range holding only the moved item follows it.
+    pub fn shift(&self, range: Range<usize>) -> Range<usize> {
+        match *self {
+            ListChange::Insert {index, ..} => insert(range, index),
+            ListChange::Remove {index, ..} => remove(range, index),
+            ListChange::Move {from, to} => {
+                let inside = range.start <= from && from < range.end;
+                let range = remove(range, from);
+                if inside && range.start == range.end {
+                    to..to + 1
+                } else if inside && range.start <= to && to <= range.end {
+                    range.start..range.end + 1
+                } else {
+                    insert(range, to)
+                }
+            }
+            _ => range,
+        }
+    }
+
+    /// Computes the range covering the same items before the change.
+    ///
+    /// This is the inverse of `shift`, except when removal maps different ranges to the same one.
+    pub fn unshift(&self, range: Range<usize>) -> Range<usize> {
+        match *self {
+            ListChange::Insert {index, ..} => remove(range, index),
+            ListChange::Remove {index, ..} => insert(range, index),
+            ListChange::Move {from, to} => ListChange::<T>::Move {from: to, to: from}.shift(range),
+            _ => range,
+        }
+    }
+}
+
+/// Computes the range covering the same items after inserting an item.
+fn insert(range: Range<usize>, index: usize) -> Range<usize> {
+    let start = if range.start >= index {range.start + 1} else {range.start};
+    let end = if range.end > index || range.start >= index {range.end + 1} else {range.end};
+    start..end
+}
+
+/// Computes the range covering the same items after removing an item.
+fn remove(range: Range<usize>, index: usize) -> Range<usize> {
+    let start = if range.start > index {range.start - 1} else {range.start};
+    let end = if range.end > index {range.end - 1} else {range.end};
+    start..end
+}
+
+/// The kind of modification made by a list modifier.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ListOp<G> {
+    /// Inserts a generated value.
+    Insert(G),
+    /// Removes an item.
+    Remove,
+    /// Swaps two items.
+    Swap,
+    /// Moves an item to another index.
+    Move,
+    /// Reverses the items of a sub-range.
+    Reverse,
+    /// Replaces an item with a generated value.
+    Replace(G),
+}
+
+/// Modifies a list, optionally restricted to a range.
+///
+/// The range is updated by `redo_meaning` and `undo_meaning`
+/// when any list modifier in the same context inserts, removes or moves items.
+/// Since removal can map different ranges to the same one,
+/// previous ranges are kept on a stack until the change is undone.
+/// The stack keeps the latest `HISTORY` ranges,
+/// such that changes that are never undone do not accumulate.
+/// Undoing a change without a previous range on the stack shifts the range back instead.
+///
+/// Inserted items are always placed inside the range,
+/// which requires at least two items, since the boundaries are outside of it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ListModifier<G> {
+    /// The kind of modification.
+    pub op: ListOp<G>,
+    range: Option<Range<usize>>,
+    history: VecDeque<Range<usize>>,
+}
+
+/// The maximum number of previous ranges kept by a list modifier.
+pub const HISTORY: usize = 64;
+
+impl<G> ListModifier<G> {
+    /// Creates a new list modifier acting on the whole list.
+    pub fn new(op: ListOp<G>) -> ListModifier<G> {
+        ListModifier {op, range: None, history: VecDeque::new()}
+    }
+
+    /// Restricts the modifier to a range of the list.
+    ///
+    /// Use `i..i + 1` to hold a single index.
+    pub fn within(mut self, range: Range<usize>) -> ListModifier<G> {
+        self.set_range(Some(range));
+        self
+    }
+
+    /// Returns the range of the list to modify, or `None` for the whole list.
+    pub fn range(&self) -> Option<Range<usize>> {self.range.clone()}
+
+    /// Sets the range of the list to modify, or `None` for the whole list.
+    ///
+    /// This forgets the previous ranges, such that undoing earlier changes shifts the range back.
+    pub fn set_range(&mut self, range: Option<Range<usize>>) {
+        self.range = range;
+        self.history.clear();
+    }
+
+    /// Returns the range of a list with some length that the modifier acts on.
+    pub fn bounds(&self, len: usize) -> Range<usize> {
+        match self.range {
+            Some(ref range) => range.start.min(len)..range.end.min(len),
+            None => 0..len,
+        }
+    }
+}
+
+impl<T, G> Modifier<Vec<T>> for ListModifier<G>
+    where T: Clone, G: Generator<Output = T>
+{
+    type Change = ListChange<T>;
+    fn modify(&mut self, obj: &mut Vec<T>) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut Vec<T>, rng: &mut dyn RngCore) -> Self::Change {
+        let Range {start, end} = self.bounds(obj.len());
+        let n = end.saturating_sub(start);
+        let change = match self.op {
+            ListOp::Insert(ref mut g) => {
+                // Indices strictly inside the range, up to the end of the list.
+                let (low, high) = match self.range {
+                    Some(ref range) => {
+                        (range.start + 1, range.end.saturating_sub(1).min(obj.len()))
+                    }
+                    None => (0, obj.len()),
+                };
+                if low <= high {
+                    let index = rng.gen_range(low, high + 1);
+                    ListChange::Insert {index, value: g.generate_with(rng)}
+                } else {
+                    ListChange::Unchanged
+                }
+            }
+            ListOp::Remove if n > 0 => {
+                let index = rng.gen_range(start, end);
+                ListChange::Remove {index, value: obj[index].clone()}
+            }
+            ListOp::Swap if n > 1 => {
+                let a = rng.gen_range(start, end);
+                let mut b = rng.gen_range(start, end - 1);
+                if b >= a {b += 1}
+                ListChange::Swap(a, b)
+            }
+            ListOp::Move if n > 1 => {
+                let from = rng.gen_range(start, end);
+                let mut to = rng.gen_range(start, end - 1);
+                if to >= from {to += 1}
+                ListChange::Move {from, to}
+            }
+            ListOp::Reverse if n > 1 => {
+                let a = rng.gen_range(start, end - 1);
+                let b = rng.gen_range(a + 2, end + 1);
+                ListChange::Reverse(a, b)
+            }
+            ListOp::Replace(ref mut g) if n > 0 => {
+                let index = rng.gen_range(start, end);
+                ListChange::Replace {index, old: obj[index].clone(), new: g.generate_with(rng)}
+            }
+            _ => ListChange::Unchanged,
+        };
+        self.redo(&change, obj);
+        change
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut Vec<T>) {
+        match *change {
+            ListChange::Unchanged => {}
+            ListChange::Insert {index, ..} => {obj.remove(index);}
+            ListChange::Remove {index, ref value} => obj.insert(index, value.clone()),
+            ListChange::Swap(a, b) => obj.swap(a, b),
+            ListChange::Move {from, to} => {
+                let item = obj.remove(to);
+                obj.insert(from, item);
+            }
+            ListChange::Reverse(a, b) => obj[a..b].reverse(),
+            ListChange::Replace {index, ref old, ..} => obj[index] = old.clone(),
+        }
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut Vec<T>) {
+        match *change {
+            ListChange::Unchanged => {}
+            ListChange::Insert {index, ref value} => obj.insert(index, value.clone()),
+            ListChange::Remove {index, ..} => {obj.remove(index);}
+            ListChange::Swap(a, b) => obj.swap(a, b),
+            ListChange::Move {from, to} => {
+                let item = obj.remove(from);
+                obj.insert(to, item);
+            }
+            ListChange::Reverse(a, b) => obj[a..b].reverse(),
+            ListChange::Replace {index, ref new, ..} => obj[index] = new.clone(),
+        }
+    }
+    fn undo_meaning(&mut self, change: &Self::Change) {
+        if !change.is_structural() {return}
+        if let Some(range) = self.range.take() {
+            self.range = Some(match self.history.pop_back() {
+                Some(previous) => previous,
+                None => change.unshift(range),
+            });
+        }
+    }
+    fn redo_meaning(&mut self, change: &Self::Change) {
+        if !change.is_structural() {return}
+        if let Some(range) = self.range.take() {
+            if self.history.len() == HISTORY {self.history.pop_front();}
+            self.history.push_back(range.clone());
+            self.range = Some(change.shift(range));
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Modifiers for numbers.
+//!
+//! `NumberModifier` works with all primitive integer and float types.
+//! The change stores the old and new value, such that `undo` and `redo` are exact.
+
+use std::fmt::Debug;
+
+use rand::{Rng, RngCore};
+use rand::distributions::{Cauchy, Distribution, Normal, Uniform};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use float;
+use Modifier;
+
+/// Implemented by primitive number types.
+pub trait Number: Copy + PartialOrd + Debug {
+    /// The number one.
+    fn one() -> Self;
+    /// Converts to `f64`, possibly losing precision.
+    fn to_f64(self) -> f64;
+    /// Converts from `f64`, rounding and saturating integers.
+    fn from_f64(x: f64) -> Self;
+    /// Adds, saturating integers.
+    fn add(self, other: Self) -> Self;
+    /// Subtracts, saturating integers.
+    fn sub(self, other: Self) -> Self;
+    /// The number of bits that can be flipped.
+    ///
+    /// This is all bits of integers and the mantissa of floats,
+    /// such that flipping a bit of a finite float keeps it finite.
+    fn bits() -> u32;
+    /// Flips a bit.
+    fn flip(self, bit: u32) -> Self;
+    /// Picks a uniformly random number in `[min, max]`.
+    fn uniform(min: Self, max: Self, rng: &mut dyn RngCore) -> Self;
+}
+
+macro_rules! int_number {
+    ($($t:ident: $signed:expr),*) => {$(
+        impl Number for $t {
+            fn one() -> $t {1}
+            fn to_f64(self) -> f64 {self as f64}
+            fn from_f64(x: f64) -> $t {x.round() as $t}
+            fn add(self, other: $t) -> $t {self.saturating_add(other)}
+            fn sub(self, other: $t) -> $t {self.saturating_sub(other)}
+            fn bits() -> u32 {$t::BITS}
+            fn flip(self, bit: u32) -> $t {self ^ (1 << bit)}
+            fn uniform(min: $t, max: $t, rng: &mut dyn RngCore) -> $t {
+                // Maps to `u128` preserving order, by flipping the sign bit of signed integers.
+                let sign: u128 = if $signed {1 << 127} else {0};
+                let low = (min as i128 as u128) ^ sign;
+                let high = (max as i128 as u128) ^ sign;
+                if high <= low {return min}
+                let x = low + uniform_u128(high - low, rng);
+                (x ^ sign) as i128 as $t
+            }
+        }
+    )*}
+}
+
+int_number!(i8: true, i16: true, i32: true, i64: true, i128: true, isize: true);
+int_number!(u8: false, u16: false, u32: false, u64: false, u128: false, usize: false);
+
+macro_rules! float_number {
+    ($($t:ident: $mantissa:expr),*) => {$(
+        impl Number for $t {
+            fn one() -> $t {1.0}
+            fn to_f64(self) -> f64 {self as f64}
+            fn from_f64(x: f64) -> $t {x as $t}
+            fn add(self, other: $t) -> $t {self + other}
+            fn sub(self, other: $t) -> $t {self - other}
+            fn bits() -> u32 {$mantissa}
+            fn flip(self, bit: u32) -> $t {$t::from_bits(self.to_bits() ^ (1 << bit))}
+            fn uniform(min: $t, max: $t, rng: &mut dyn RngCore) -> $t {
+                if max <= min {return min}
+                Uniform::new_inclusive(min, max).sample(rng)
+            }
+        }
+    )*}
+}
+
+float_number!(f32: 23, f64: 52);
+
+/// Picks a uniformly random number in `[0, range]`.
+fn uniform_u128(range: u128, rng: &mut dyn RngCore) -> u128 {
+    let mut next = || (rng.next_u64() as u128) << 64 | rng.next_u64() as u128;
+    if range == u128::MAX {return next()}
+    let n = range + 1;
+    // Rejects the top values that would make some numbers more likely.
+    let limit = u128::MAX - (u128::MAX - n + 1) % n;
+    loop {
+        let x = next();
+        if x <= limit {return x % n}
+    }
+}
+
+fn clamp<N: Number>(x: N, min: N, max: N) -> N {
+    if x < min {min} else if x > max {max} else {x}
+}
+
+/// The kind of modification made by a number modifier.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NumberOp {
+    /// Adds the step.
+    Increment,
+    /// Subtracts the step.
+    Decrement,
+    /// Adds normally distributed noise with standard deviation sigma.
+    Gaussian,
+    /// Adds Cauchy distributed noise with scale sigma.
+    ///
+    /// This makes large jumps more often than `Gaussian`.
+    Cauchy,
+    /// Picks a uniformly random number within bounds.
+    Uniform,
+    /// Flips a random bit.
+    BitFlip,
+}
+
+/// Stores a number change.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "N: Number + Serialize",
+    deserialize = "N: Number + Deserialize<'de>"
+)))]
+pub struct NumberChange<N> {
+    /// The value before the change.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub old: N,
+    /// The value after the change.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub new: N,
+}
+
+/// Modifies a number within bounds `[min, max]`.
+///
+/// Results outside the bounds are clamped.
+/// With an adaptation factor, sigma is adapted by the 1/5th success rule from feedback:
+/// It is multiplied by the factor when utility increased,
+/// and divided by the fourth root of the factor otherwise,
+/// such that it is stable when one in five modifications succeeds.
+/// This suits optimizers that reject worse modifications, e.g. `SimulatedAnnealing`,
+/// since `ModifyOptimizer` keeps worse modifications within a try.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "N: Number + Serialize",
+    deserialize = "N: Number + Deserialize<'de>"
+)))]
+pub struct NumberModifier<N> {
+    /// The kind of modification.
+    pub op: NumberOp,
+    /// The lower bound.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub min: N,
+    /// The upper bound.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub max: N,
+    /// The step of `Increment` and `Decrement`.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub step: N,
+    /// The scale of `Gaussian` and `Cauchy` noise.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub sigma: f64,
+    /// The factor for adapting sigma, usually around `1.5`.
+    #[cfg_attr(feature = "serde", serde(with = "float::option"))]
+    pub adaptation: Option<f64>,
+}
+
+impl<N: Number> NumberModifier<N> {
+    /// Creates a new number modifier with step one and sigma one.
+    pub fn new(op: NumberOp, min: N, max: N) -> NumberModifier<N> {
+        NumberModifier {op, min, max, step: N::one(), sigma: 1.0, adaptation: None}
+    }
+
+    /// Sets the step of `Increment` and `Decrement`.
+    pub fn step(self, step: N) -> NumberModifier<N> {
+        NumberModifier {step, ..self}
+    }
+
+    /// Sets the scale of `Gaussian` and `Cauchy` noise.
+    pub fn sigma(self, sigma: f64) -> NumberModifier<N> {
+        NumberModifier {sigma, ..self}
+    }
+
+    /// Adapts sigma by the 1/5th success rule using a factor.
+    pub fn adaptive(self, factor: f64) -> NumberModifier<N> {
+        NumberModifier {adaptation: Some(factor), ..self}
+    }
+}
+
+impl<N: Number> Modifier<N> for NumberModifier<N> {
+    type Change = NumberChange<N>;
+    fn modify(&mut self, obj: &mut N) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut N, rng: &mut dyn RngCore) -> Self::Change {
+        let old = *obj;
+        let new = match self.op {
+            NumberOp::Increment => old.add(self.step),
+            NumberOp::Decrement => old.sub(self.step),
+            NumberOp::Gaussian => {
+                let noise = Normal::new(0.0, 1.0).sample(rng);
+                N::from_f64(old.to_f64() + self.sigma * noise)
+            }
+            NumberOp::Cauchy => {
+                let noise = Cauchy::new(0.0, 1.0).sample(rng);
+                N::from_f64(old.to_f64() + self.sigma * noise)
+            }
+            NumberOp::Uniform => N::uniform(self.min, self.max, rng),
+            NumberOp::BitFlip => old.flip(rng.gen_range(0, N::bits())),
+        };
+        let new = clamp(new, self.min, self.max);
+        *obj = new;
+        NumberChange {old, new}
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut N) {*obj = change.old}
+    fn redo(&mut self, change: &Self::Change, obj: &mut N) {*obj = change.new}
+    fn feedback(&mut self, _change: &Self::Change, delta: f64) {
+        if let Some(factor) = self.adaptation {
+            if delta > 0.0 {
+                self.sigma *= factor;
+            } else {
+                self.sigma /= factor.powf(0.25);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Observers of optimization runs.
+
+use Stop;
+
+/// Implemented by objects that observe an optimization run.
+///
+/// All methods have empty default implementations,
+/// such that an observer only needs to implement the events it is interested in.
+pub trait Observer {
+    /// Called at the start of a run with the utility of the initial object.
+    fn start(&mut self, _utility: f64) {}
+    /// Called at the start of a new try, iteration or generation.
+    fn next_try(&mut self, _index: usize) {}
+    /// Called after a modification with the utility of the modified object.
+    fn change(&mut self, _utility: f64) {}
+    /// Called when a new best utility is found.
+    fn improve(&mut self, _utility: f64) {}
+    /// Called after undoing a number of changes.
+    fn backtrack(&mut self, _changes: usize) {}
+    /// Called at the end of a run with the reason for stopping.
+    fn stop(&mut self, _stop: &Stop) {}
+}
+
+/// Observes nothing.
+impl Observer for () {}
+
+/// Forwards events to every observer.
+impl<O: Observer> Observer for Vec<O> {
+    fn start(&mut self, utility: f64) {for it in self {it.start(utility)}}
+    fn next_try(&mut self, index: usize) {for it in self {it.next_try(index)}}
+    fn change(&mut self, utility: f64) {for it in self {it.change(utility)}}
+    fn improve(&mut self, utility: f64) {for it in self {it.improve(utility)}}
+    fn backtrack(&mut self, changes: usize) {for it in self {it.backtrack(changes)}}
+    fn stop(&mut self, stop: &Stop) {for it in self {it.stop(stop)}}
+}
+
+impl<O: Observer + ?Sized> Observer for &mut O {
+    fn start(&mut self, utility: f64) {(**self).start(utility)}
+    fn next_try(&mut self, index: usize) {(**self).next_try(index)}
+    fn change(&mut self, utility: f64) {(**self).change(utility)}
+    fn improve(&mut self, utility: f64) {(**self).improve(utility)}
+    fn backtrack(&mut self, changes: usize) {(**self).backtrack(changes)}
+    fn stop(&mut self, stop: &Stop) {(**self).stop(stop)}
+}
+
+/// Logs start, new best utilities and stop reason to standard error.
+#[derive(Copy, Clone, Debug)]
+pub struct Log;
+
+impl Observer for Log {
+    fn start(&mut self, utility: f64) {eprintln!("start, utility {}", utility)}
+    fn improve(&mut self, utility: f64) {eprintln!("improve, utility {}", utility)}
+    fn stop(&mut self, stop: &Stop) {eprintln!("stop, {:?}", stop)}
+}
+
+/// Collects a convergence trace of best utility per utility evaluation.
+#[derive(Clone, Debug, Default)]
+pub struct Trace {
+    /// The number of utility evaluations so far.
+    pub evaluations: usize,
+    /// Pairs of evaluation count and best utility, one per improvement.
+    pub best: Vec<(usize, f64)>,
+}
+
+impl Observer for Trace {
+    fn start(&mut self, utility: f64) {
+        self.evaluations += 1;
+        self.best.push((self.evaluations, utility));
+    }
+    fn change(&mut self, _utility: f64) {self.evaluations += 1}
+    fn improve(&mut self, utility: f64) {self.best.push((self.evaluations, utility))}
+}
+
+/// Counts events, including the number of utility evaluations.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Counter {
+    /// The number of utility evaluations.
+    pub evaluations: usize,
+    /// The number of tries, iterations or generations.
+    pub tries: usize,
+    /// The number of improvements of best utility.
+    pub improvements: usize,
+    /// The number of undone changes when backtracking.
+    pub backtracks: usize,
+}
+
+impl Observer for Counter {
+    fn start(&mut self, _utility: f64) {self.evaluations += 1}
+    fn next_try(&mut self, _index: usize) {self.tries += 1}
+    fn change(&mut self, _utility: f64) {self.evaluations += 1}
+    fn improve(&mut self, _utility: f64) {self.improvements += 1}
+    fn backtrack(&mut self, changes: usize) {self.backtracks += changes}
+}
@@ -0,0 +1,151 @@
+//! Parallel optimization and batch evaluation.
+//!
+//! This module requires the `parallel` feature.
+//!
+//! `ModifyOptimizer::optimize_parallel` runs independent tries on clones of the object
+//! across threads and applies the best sequence of changes to the object.
+//! `GeneticOptimizer::evolve_parallel` and `Nsga2::evolve_parallel`
+//! evaluate each generation of children in parallel.
+//! `utilities` evaluates a batch of objects in parallel,
+//! and `Parallel` sums up sub-utilities in parallel.
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use rayon::prelude::*;
+
+use {
+    Crossover, Generator, GeneticOptimizer, Modifier, ModifyOptimizer, MultiUtility,
+    Nsga2, Observer, ParetoArchive, Stop, Utility,
+};
+
+/// Computes the utility of each object in parallel.
+pub fn utilities<T, U>(utility: &U, objs: &[T]) -> Vec<f64>
+    where T: Sync, U: Utility<T> + Sync + ?Sized
+{
+    objs.par_iter().map(|it| utility.utility(it)).collect()
+}
+
+/// Computes the utilities of each object in parallel.
+pub fn multi_utilities<T, U>(utility: &U, objs: &[T]) -> Vec<Vec<f64>>
+    where T: Sync, U: MultiUtility<T> + Sync + ?Sized
+{
+    objs.par_iter().map(|it| utility.utilities(it)).collect()
+}
+
+/// Evaluates sub-utilities in parallel.
+///
+/// This pays off when the sub-utilities are expensive to compute.
+#[derive(Clone, Debug)]
+pub struct Parallel<U>(pub U);
+
+/// Sums up utility from multiple sub-terms in parallel.
+impl<T: Sync, U: Utility<T> + Sync> Utility<T> for Parallel<Vec<U>> {
+    fn utility(&self, obj: &T) -> f64 {
+        self.0.par_iter().map(|it| it.utility(obj)).sum()
+    }
+}
+
+/// Uses each sub-utility as an objective, computed in parallel.
+impl<T: Sync, U: Utility<T> + Sync> MultiUtility<T> for Parallel<Vec<U>> {
+    fn utilities(&self, obj: &T) -> Vec<f64> {
+        self.0.par_iter().map(|it| it.utility(obj)).collect()
+    }
+}
+
+impl<M, U, O: Observer> ModifyOptimizer<M, U, O> {
+    /// Modifies an object by running tries in parallel,
+    /// and returns the best change with the reason for stopping.
+    ///
+    /// The tries are split into one task per thread,
+    /// each optimizing a clone of the object using a clone of the modifier.
+    /// Every task checks the termination criterion on its own,
+    /// e.g. `Termination::Evaluations` limits the evaluations of each task.
+    /// The best sequence of changes is redone on the object using the modifier.
+    ///
+    /// The tasks are seeded from the random number generator,
+    /// such that a fixed seed and number of threads yields identical runs.
+    /// Feedback to the cloned modifiers is discarded.
+    /// The observer only receives the start, the best improvement and the stop.
+    pub fn optimize_parallel<T>(
+        &mut self,
+        obj: &mut T,
+        rng: &mut dyn RngCore
+    ) -> (Vec<M::Change>, Stop)
+        where T: Clone + Send,
+              M: Modifier<T> + Clone + Send,
+              M::Change: Clone + Send,
+              U: Utility<T> + Sync
+    {
+        let start = self.utility.utility(obj);
+        self.observer.start(start);
+        let n = ::rayon::current_num_threads().max(1).min(self.tries.max(1));
+        let tasks: Vec<(usize, u64, T, M)> = (0..n).map(|i| {
+            let tries = self.tries / n + if i < self.tries % n {1} else {0};
+            (tries, rng.next_u64(), obj.clone(), self.modifier.clone())
+        }).collect();
+        let utility = &self.utility;
+        let depth = self.depth;
+        let termination = &self.termination;
+        let results: Vec<(f64, Vec<M::Change>, Stop)> = tasks.into_par_iter()
+            .map(|(tries, seed, mut obj, modifier)| {
+                let mut optimizer = ModifyOptimizer {
+                    modifier,
+                    utility,
+                    tries,
+                    depth,
+                    termination: termination.clone(),
+                    observer: (),
+                };
+                let mut steps = optimizer.steps_with(&mut obj, StdRng::seed_from_u64(seed));
+                for _ in &mut steps {}
+                let best_utility = steps.progress().best_utility;
+                let (changes, stop) = steps.finish();
+                (best_utility, changes, stop)
+            }).collect();
+        let mut best_utility = start;
+        let mut best = vec![];
+        let mut stop = Stop::Completed;
+        for (utility, changes, task_stop) in results {
+            if best_utility < utility {
+                best_utility = utility;
+                best = changes;
+            }
+            if stop == Stop::Completed {stop = task_stop}
+        }
+        for change in &best {
+            self.modifier.redo(change, obj);
+            self.modifier.redo_meaning(change);
+        }
+        if start < best_utility {self.observer.improve(best_utility)}
+        self.observer.stop(&stop);
+        (best, stop)
+    }
+}
+
+impl<T, G, C, M, U, O> GeneticOptimizer<G, C, M, U, O>
+    where T: Clone + Sync, G: Generator<Output = T>, C: Crossover<T>, M: Modifier<T>,
+          U: Utility<T> + Sync, O: Observer
+{
+    /// Evolves a population like `evolve`, evaluating each generation in parallel.
+    ///
+    /// Children are evaluated a generation at a time, such that when the termination
+    /// criterion is met, the rest of the generation is evaluated but discarded.
+    pub fn evolve_parallel(&mut self, rng: &mut dyn RngCore) -> (Vec<(T, f64)>, Stop) {
+        let batch = self.population;
+        self.evolve_in(rng, batch, |utility, objs| utilities(utility, objs))
+    }
+}
+
+impl<T, G, C, M, U, O> Nsga2<G, C, M, U, O>
+    where T: Clone + Sync, G: Generator<Output = T>, C: Crossover<T>, M: Modifier<T>,
+          U: MultiUtility<T> + Sync, O: Observer
+{
+    /// Evolves a population like `evolve`, evaluating each generation in parallel.
+    ///
+    /// Children are evaluated a generation at a time, such that when the termination
+    /// criterion is met, the rest of the generation is evaluated but discarded.
+    pub fn evolve_parallel(&mut self, rng: &mut dyn RngCore) -> (ParetoArchive<T>, Stop) {
+        let batch = self.population;
+        self.evolve_in(rng, batch, |utility, objs| multi_utilities(utility, objs))
+    }
+}
@@ -0,0 +1,327 @@
+//! Multi-objective utility and Pareto-front optimization.
+//!
+//! All objectives are maximized.
+
+use rand::{Rng, RngCore};
+
+use genetic::compare;
+use {Crossover, Generator, Modifier, Observer, Progress, Stop, Termination, Utility};
+
+/// Implemented by objects that measure multiple utilities of an object.
+///
+/// Unlike `Utility`, the objectives are not summed up,
+/// which keeps the trade-offs between them visible.
+pub trait MultiUtility<T> {
+    /// Computes the utility of each objective.
+    fn utilities(&self, obj: &T) -> Vec<f64>;
+}
+
+/// Uses each sub-utility as an objective.
+impl<T, U: Utility<T>> MultiUtility<T> for Vec<U> {
+    fn utilities(&self, obj: &T) -> Vec<f64> {
+        self.iter().map(|it| it.utility(obj)).collect()
+    }
+}
+
+/// Uses each sub-utility as an objective.
+impl<T, U: Utility<T>> MultiUtility<T> for [U] {
+    fn utilities(&self, obj: &T) -> Vec<f64> {
+        self.iter().map(|it| it.utility(obj)).collect()
+    }
+}
+
+/// Returns `true` if `a` is at least as good as `b` in all objectives and better in one.
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut better = false;
+    for (x, y) in a.iter().zip(b.iter()) {
+        if x < y {return false}
+        if x > y {better = true}
+    }
+    better
+}
+
+/// Sorts objective vectors into fronts of non-dominated indices.
+///
+/// The first front contains the indices that are not dominated by any other.
+pub fn non_dominated_sort(objectives: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated_by: Vec<Vec<usize>> = vec![vec![]; n];
+    let mut counts = vec![0; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            if dominates(&objectives[i], &objectives[j]) {
+                dominated_by[i].push(j);
+                counts[j] += 1;
+            } else if dominates(&objectives[j], &objectives[i]) {
+                dominated_by[j].push(i);
+                counts[i] += 1;
+            }
+        }
+    }
+    let mut fronts = vec![];
+    let mut front: Vec<usize> = (0..n).filter(|&i| counts[i] == 0).collect();
+    while !front.is_empty() {
+        let mut next = vec![];
+        for &i in &front {
+            for &j in &dominated_by[i] {
+                counts[j] -= 1;
+                if counts[j] == 0 {next.push(j)}
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// Computes the crowding distance of each index in a front.
+///
+/// Boundary points get infinite distance.
+/// A larger distance means the point is in a less crowded region.
+#[allow(clippy::needless_range_loop)]
+pub fn crowding_distance(objectives: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let n = front.len();
+    let mut distance = vec![0.0; n];
+    if n == 0 {return distance}
+    let m = objectives[front[0]].len();
+    let mut order: Vec<usize> = (0..n).collect();
+    for k in 0..m {
+        let value = |i: usize| objectives[front[i]][k];
+        order.sort_by(|&a, &b| compare(value(a), value(b)));
+        let min = value(order[0]);
+        let max = value(order[n - 1]);
+        distance[order[0]] = f64::INFINITY;
+        distance[order[n - 1]] = f64::INFINITY;
+        if max <= min {continue}
+        for w in 1..n - 1 {
+            distance[order[w]] += (value(order[w + 1]) - value(order[w - 1])) / (max - min);
+        }
+    }
+    distance
+}
+
+/// Stores objects that are not dominated by each other.
+#[derive(Clone, Debug)]
+pub struct ParetoArchive<T> {
+    /// The non-dominated objects with their objectives.
+    pub members: Vec<(T, Vec<f64>)>,
+}
+
+impl<T> Default for ParetoArchive<T> {
+    fn default() -> ParetoArchive<T> {ParetoArchive::new()}
+}
+
+impl<T> ParetoArchive<T> {
+    /// Creates a new empty archive.
+    pub fn new() -> ParetoArchive<T> {ParetoArchive {members: vec![]}}
+
+    /// Returns `true` if the objectives are dominated by or equal to a member.
+    pub fn is_dominated(&self, objectives: &[f64]) -> bool {
+        self.members.iter().any(|it| {
+            dominates(&it.1, objectives) || &it.1[..] == objectives
+        })
+    }
+
+    /// Inserts an object, unless it is dominated by or equal to a member.
+    ///
+    /// Members dominated by the new object are removed.
+    /// Returns `true` if the object was inserted.
+    pub fn insert(&mut self, obj: T, objectives: Vec<f64>) -> bool {
+        if self.is_dominated(&objectives) {return false}
+        self.members.retain(|it| !dominates(&objectives, &it.1));
+        self.members.push((obj, objectives));
+        true
+    }
+
+    /// Returns the objectives of the members.
+    pub fn objectives(&self) -> Vec<Vec<f64>> {
+        self.members.iter().map(|it| it.1.clone()).collect()
+    }
+}
+
+/// Measures the utility of an object by Pareto dominance against reference objectives.
+///
+/// The utility is the number of reference points the object dominates,
+/// minus the number of reference points that dominate it.
+/// This turns multiple objectives into a single utility without weighting them,
+/// such that optimizers for `Utility` can push objects toward a front, e.g. of an archive.
+#[derive(Clone, Debug)]
+pub struct Dominance<U> {
+    /// The measured objectives.
+    pub utility: U,
+    /// The objectives to compare against.
+    pub reference: Vec<Vec<f64>>,
+}
+
+impl<U> Dominance<U> {
+    /// Compares against the members of an archive.
+    pub fn archive<T>(utility: U, archive: &ParetoArchive<T>) -> Dominance<U> {
+        Dominance {utility, reference: archive.objectives()}
+    }
+}
+
+impl<T, U: MultiUtility<T>> Utility<T> for Dominance<U> {
+    fn utility(&self, obj: &T) -> f64 {
+        let objectives = self.utility.utilities(obj);
+        let mut sum = 0.0;
+        for it in &self.reference {
+            if dominates(&objectives, it) {sum += 1.0}
+            else if dominates(it, &objectives) {sum -= 1.0}
+        }
+        sum
+    }
+}
+
+/// Evolves a population toward the Pareto front using NSGA-II.
+///
+/// The initial population is generated by the generator.
+/// Parents are picked by binary tournament on front rank and crowding distance.
+/// A modifier is used as mutation, where the change is discarded.
+///
+/// Termination on target utility and stagnation uses the sum of objectives,
+/// which is also the utility reported to the observer.
+pub struct Nsga2<G, C, M, U, O = ()> {
+    /// Generates the initial population.
+    pub generator: G,
+    /// Recombines parents.
+    pub crossover: C,
+    /// Mutates children.
+    pub mutation: M,
+    /// The measured objectives.
+    pub utility: U,
+    /// The size of the population.
+    pub population: usize,
+    /// The probability of mutating a child.
+    pub mutation_rate: f64,
+    /// The number of generations.
+    pub generations: usize,
+    /// The criterion for stopping before all generations are completed.
+    pub termination: Termination,
+    /// Observes the optimization.
+    ///
+    /// Each generation is reported as a try and each child as a change.
+    pub observer: O,
+}
+
+impl<T, G, C, M, U, O> Nsga2<G, C, M, U, O>
+    where T: Clone, G: Generator<Output = T>, C: Crossover<T>, M: Modifier<T>, U: MultiUtility<T>,
+          O: Observer
+{
+    /// Evolves a population and returns its first front, together with the reason for stopping.
+    pub fn evolve(&mut self, rng: &mut dyn RngCore) -> (ParetoArchive<T>, Stop) {
+        self.evolve_in(rng, 1, |utility, objs| {
+            objs.iter().map(|it| utility.utilities(it)).collect()
+        })
+    }
+
+    /// Evolves a population, evaluating up to `batch` children at once.
+    pub(crate) fn evolve_in<F>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        batch: usize,
+        evaluate: F
+    ) -> (ParetoArchive<T>, Stop)
+        where F: Fn(&U, &[T]) -> Vec<Vec<f64>>
+    {
+        let mut objs: Vec<T> = (0..self.population)
+            .map(|_| self.generator.generate_with(rng)).collect();
+        let mut objectives = evaluate(&self.utility, &objs);
+        if objs.is_empty() {return (ParetoArchive::new(), Stop::Completed)}
+        let mut progress = Progress::new(objectives[0].iter().sum());
+        self.observer.start(progress.best_utility);
+        for it in &objectives[1..] {
+            let utility = it.iter().sum();
+            self.observer.change(utility);
+            if progress.best_utility < utility {self.observer.improve(utility)}
+            progress.evaluate(utility);
+        }
+        let mut stop = None;
+        for i in 0..self.generations {
+            self.observer.next_try(i);
+            let (rank, crowding) = rank_and_crowding(&objectives);
+            let n = objs.len();
+            let tournament = |rng: &mut dyn RngCore| {
+                let a = rng.gen_range(0, n);
+                let b = rng.gen_range(0, n);
+                if rank[a] < rank[b] || rank[a] == rank[b] && crowding[a] > crowding[b] {a} else {b}
+            };
+            let mut children = vec![];
+            let mut children_objectives = vec![];
+            while children.len() < self.population {
+                let m = batch.max(1).min(self.population - children.len());
+                let mut batch_children = Vec::with_capacity(m);
+                for _ in 0..m {
+                    let a = tournament(rng);
+                    let b = tournament(rng);
+                    let mut child = self.crossover.crossover_with(&objs[a], &objs[b], rng);
+                    if rng.gen::<f64>() < self.mutation_rate {
+                        self.mutation.modify_with(&mut child, rng);
+                    }
+                    batch_children.push(child);
+                }
+                let batch_objectives = evaluate(&self.utility, &batch_children);
+                for (child, child_objectives) in batch_children.into_iter().zip(batch_objectives) {
+                    let utility = child_objectives.iter().sum();
+                    self.observer.change(utility);
+                    if progress.best_utility < utility {self.observer.improve(utility)}
+                    progress.evaluate(utility);
+                    children.push(child);
+                    children_objectives.push(child_objectives);
+                    stop = self.termination.check(&progress);
+                    if stop.is_some() {break}
+                }
+                if stop.is_some() {break}
+            }
+            objs.extend(children);
+            objectives.extend(children_objectives);
+            let (objs2, objectives2) = survivors(objs, objectives, self.population);
+            objs = objs2;
+            objectives = objectives2;
+            if stop.is_some() {break}
+        }
+        let mut archive = ParetoArchive::new();
+        for (obj, objectives) in objs.into_iter().zip(objectives) {
+            archive.insert(obj, objectives);
+        }
+        let stop = stop.unwrap_or(Stop::Completed);
+        self.observer.stop(&stop);
+        (archive, stop)
+    }
+}
+
+fn rank_and_crowding(objectives: &[Vec<f64>]) -> (Vec<usize>, Vec<f64>) {
+    let mut rank = vec![0; objectives.len()];
+    let mut crowding = vec![0.0; objectives.len()];
+    for (r, front) in non_dominated_sort(objectives).iter().enumerate() {
+        let distance = crowding_distance(objectives, front);
+        for (&i, d) in front.iter().zip(distance) {
+            rank[i] = r;
+            crowding[i] = d;
+        }
+    }
+    (rank, crowding)
+}
+
+fn survivors<T>(
+    objs: Vec<T>,
+    objectives: Vec<Vec<f64>>,
+    size: usize
+) -> (Vec<T>, Vec<Vec<f64>>) {
+    let mut keep = vec![];
+    for front in non_dominated_sort(&objectives) {
+        if keep.len() + front.len() <= size {
+            keep.extend(front);
+        } else {
+            let distance = crowding_distance(&objectives, &front);
+            let mut order: Vec<usize> = (0..front.len()).collect();
+            order.sort_by(|&a, &b| compare(distance[b], distance[a]));
+            let rest = size - keep.len();
+            keep.extend(order.into_iter().take(rest).map(|i| front[i]));
+        }
+        if keep.len() >= size {break}
+    }
+    keep.sort();
+    let mut objs: Vec<Option<T>> = objs.into_iter().map(Some).collect();
+    let mut objectives: Vec<Option<Vec<f64>>> = objectives.into_iter().map(Some).collect();
+    keep.into_iter().map(|i| (objs[i].take().unwrap(), objectives[i].take().unwrap())).unzip()
+}
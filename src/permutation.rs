@@ -0,0 +1,193 @@
+//! Modifiers for permutations.
+//!
+//! Permutation modifiers reorder the items of a `Vec<T>` without adding or removing items.
+//! Changes store only indices, except scrambling which stores the order of the segment,
+//! such that `undo` and `redo` take time proportional to the number of moved items.
+
+use rand::{Rng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use {Generator, Modifier};
+
+/// Stores a change made to a permutation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PermutationChange {
+    /// The permutation was not changed, because there were too few items.
+    Unchanged,
+    /// Reversed the segment `[start, end)`.
+    Reverse(usize, usize),
+    /// Exchanged the adjacent segments `[a, b)` and `[b, c)`.
+    Exchange(usize, usize, usize),
+    /// Moved a segment by removing it and inserting it.
+    Move {
+        /// The start of the segment before moving.
+        from: usize,
+        /// The length of the segment.
+        len: usize,
+        /// The start of the segment after moving.
+        to: usize,
+    },
+    /// Swapped two items.
+    Swap(usize, usize),
+    /// Reordered a segment, such that item `i` of the segment came from `order[i]`.
+    Scramble {
+        /// The start of the segment.
+        start: usize,
+        /// The new order of the segment, relative to its start.
+        order: Vec<usize>,
+    },
+}
+
+/// Modifies a permutation.
+///
+/// All modifiers share the change type `PermutationChange`,
+/// such that they can be combined with `Vec<PermutationModifier>`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PermutationModifier {
+    /// Reverses a segment, which replaces two edges of a tour.
+    TwoOpt,
+    /// Exchanges two adjacent segments, which replaces three edges of a tour.
+    ThreeOpt,
+    /// Moves a segment of up to 3 items to another position.
+    OrOpt,
+    /// Swaps two items.
+    Swap,
+    /// Moves one item to another position.
+    Insertion,
+    /// Shuffles a segment of at most some length.
+    Scramble(usize),
+}
+
+impl PermutationChange {
+    /// Applies the change to a list.
+    pub fn redo<T>(&self, list: &mut [T]) {
+        match *self {
+            PermutationChange::Unchanged => {}
+            PermutationChange::Reverse(start, end) => list[start..end].reverse(),
+            PermutationChange::Exchange(a, b, c) => list[a..c].rotate_left(b - a),
+            PermutationChange::Move {from, len, to} => move_segment(list, from, len, to),
+            PermutationChange::Swap(a, b) => list.swap(a, b),
+            PermutationChange::Scramble {start, ref order} => {
+                reorder(&mut list[start..start + order.len()], order)
+            }
+        }
+    }
+
+    /// Reverts the change made to a list.
+    pub fn undo<T>(&self, list: &mut [T]) {
+        match *self {
+            PermutationChange::Exchange(a, b, c) => list[a..c].rotate_left(c - b),
+            PermutationChange::Move {from, len, to} => move_segment(list, to, len, from),
+            PermutationChange::Scramble {start, ref order} => {
+                let mut inverse = vec![0; order.len()];
+                for (i, &j) in order.iter().enumerate() {inverse[j] = i}
+                reorder(&mut list[start..start + order.len()], &inverse)
+            }
+            _ => self.redo(list),
+        }
+    }
+}
+
+fn move_segment<T>(list: &mut [T], from: usize, len: usize, to: usize) {
+    if to < from {
+        list[to..from + len].rotate_right(len)
+    } else {
+        list[from..to + len].rotate_left(len)
+    }
+}
+
+/// Reorders a list in place, such that item `i` comes from `order[i]`.
+fn reorder<T>(list: &mut [T], order: &[usize]) {
+    let mut visited = vec![false; order.len()];
+    for i in 0..order.len() {
+        if visited[i] {continue}
+        visited[i] = true;
+        let mut j = i;
+        while order[j] != i {
+            list.swap(j, order[j]);
+            j = order[j];
+            visited[j] = true;
+        }
+    }
+}
+
+impl PermutationModifier {
+    /// Picks a change for a permutation of some length.
+    pub fn pick(&self, n: usize, rng: &mut dyn RngCore) -> PermutationChange {
+        if n < 2 {return PermutationChange::Unchanged}
+        match *self {
+            PermutationModifier::TwoOpt => {
+                let start = rng.gen_range(0, n - 1);
+                let end = rng.gen_range(start + 2, n + 1);
+                PermutationChange::Reverse(start, end)
+            }
+            PermutationModifier::ThreeOpt => {
+                let a = rng.gen_range(0, n - 1);
+                let b = rng.gen_range(a + 1, n);
+                let c = rng.gen_range(b + 1, n + 1);
+                PermutationChange::Exchange(a, b, c)
+            }
+            PermutationModifier::OrOpt | PermutationModifier::Insertion => {
+                let len = if *self == PermutationModifier::OrOpt {
+                    rng.gen_range(1, (n - 1).min(3) + 1)
+                } else {1};
+                let from = rng.gen_range(0, n - len + 1);
+                let mut to = rng.gen_range(0, n - len);
+                if to >= from {to += 1}
+                PermutationChange::Move {from, len, to}
+            }
+            PermutationModifier::Swap => {
+                let a = rng.gen_range(0, n);
+                let mut b = rng.gen_range(0, n - 1);
+                if b >= a {b += 1}
+                PermutationChange::Swap(a, b)
+            }
+            PermutationModifier::Scramble(max) => {
+                let max = max.min(n);
+                if max < 2 {return PermutationChange::Unchanged}
+                let len = rng.gen_range(2, max + 1);
+                let start = rng.gen_range(0, n - len + 1);
+                let mut order: Vec<usize> = (0..len).collect();
+                for i in (1..len).rev() {
+                    order.swap(i, rng.gen_range(0, i + 1));
+                }
+                PermutationChange::Scramble {start, order}
+            }
+        }
+    }
+}
+
+impl<T> Modifier<Vec<T>> for PermutationModifier {
+    type Change = PermutationChange;
+    fn modify(&mut self, obj: &mut Vec<T>) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut Vec<T>, rng: &mut dyn RngCore) -> Self::Change {
+        let change = self.pick(obj.len(), rng);
+        change.redo(obj);
+        change
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut Vec<T>) {change.undo(obj)}
+    fn redo(&mut self, change: &Self::Change, obj: &mut Vec<T>) {change.redo(obj)}
+}
+
+/// Generates a random permutation of `0..n`.
+#[derive(Copy, Clone, Debug)]
+pub struct RandomPermutation(pub usize);
+
+impl Generator for RandomPermutation {
+    type Output = Vec<usize>;
+    fn generate(&mut self) -> Vec<usize> {
+        self.generate_with(&mut ::rand::thread_rng())
+    }
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Vec<usize> {
+        let mut list: Vec<usize> = (0..self.0).collect();
+        for i in (1..self.0).rev() {
+            list.swap(i, rng.gen_range(0, i + 1));
+        }
+        list
+    }
+}
@@ -0,0 +1,252 @@
+//! Anytime optimization by stepping through tries.
+//!
+//! A `Checkpoint` saves the state of an optimization between tries,
+//! such that it can be resumed after a process restart.
+//! With the `serde` feature, checkpoints can be saved to and loaded from files.
+//!
+//! Only `ModifyOptimizer` supports checkpoints.
+//! The state of other optimizers lives within a single run and is not saved,
+//! e.g. the temperature of `SimulatedAnnealing`, the tabu list of `TabuSearch`
+//! or the population of `GeneticOptimizer` and `Nsga2`.
+
+#[cfg(feature = "serde")]
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::io::{self, BufReader, BufWriter, Write};
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rand::RngCore;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde_json;
+
+use delta::{DeltaUtility, Evaluation, Evaluator};
+#[cfg(feature = "serde")]
+use float;
+use {Modifier, ModifyOptimizer, Observer, Progress, Stop, Utility};
+
+/// The state of an optimization after a try.
+#[derive(Clone, Debug)]
+pub struct Step<C> {
+    /// The number of completed tries.
+    pub tries: usize,
+    /// Whether the try improved the best utility.
+    pub improved: bool,
+    /// The best utility so far.
+    pub best_utility: f64,
+    /// The changes that give the best utility, relative to the initial object.
+    pub best: Vec<C>,
+}
+
+/// The state of an optimization between tries.
+///
+/// The best changes are relative to the initial object,
+/// such that redoing them on the initial object restores the best object so far.
+/// The modifier is saved with its learned statistics, e.g. of `AdaptiveModifier`,
+/// and the random number generator with its state.
+/// To save a checkpoint to a file, the random number generator must be serializable,
+/// e.g. `rand::prng::XorShiftRng` or `rand::prng::IsaacRng`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Checkpoint<C, M, R> {
+    /// The number of completed tries.
+    pub tries: usize,
+    /// The changes that give the best utility, relative to the initial object.
+    pub best: Vec<C>,
+    /// The best utility so far.
+    ///
+    /// This is saved as a string when not finite, e.g. `-inf` when rejecting infeasible objects.
+    #[cfg_attr(feature = "serde", serde(with = "float"))]
+    pub best_utility: f64,
+    /// The number of utility evaluations.
+    pub evaluations: usize,
+    /// The number of utility evaluations since the last improvement.
+    pub stagnation: usize,
+    /// The time spent optimizing.
+    pub elapsed: Duration,
+    /// The modifier.
+    pub modifier: M,
+    /// The random number generator.
+    pub rng: R,
+}
+
+#[cfg(feature = "serde")]
+impl<C: Serialize, M: Serialize, R: Serialize> Checkpoint<C, M, R> {
+    /// Saves the checkpoint to a file as JSON.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut w, self)?;
+        w.flush()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<C: DeserializeOwned, M: DeserializeOwned, R: DeserializeOwned> Checkpoint<C, M, R> {
+    /// Loads a checkpoint from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Checkpoint<C, M, R>> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+}
+
+/// Iterates through the tries of a `ModifyOptimizer`, yielding after each try.
+///
+/// Use `.filter(|it| it.improved)` to only get improvements.
+/// The object is kept in its initial state between tries.
+/// Call `finish` to apply the best changes, also when stopping early.
+pub struct Steps<'a, T: 'a, M: 'a + Modifier<T>, U: 'a, O: 'a, R> {
+    optimizer: &'a mut ModifyOptimizer<M, U, O>,
+    obj: &'a mut T,
+    rng: R,
+    start: f64,
+    progress: Progress,
+    best: Vec<M::Change>,
+    tries: usize,
+    stop: Option<Stop>,
+    evaluator: Evaluator<U, T, M::Change>,
+}
+
+impl<'a, T, M, U, O, R> Steps<'a, T, M, U, O, R>
+    where M: Modifier<T>, U: Utility<T>, O: Observer
+{
+    /// Starts stepping through the tries of an optimizer.
+    pub fn new(optimizer: &'a mut ModifyOptimizer<M, U, O>, obj: &'a mut T, rng: R) -> Self {
+        let start = optimizer.utility.utility(obj);
+        optimizer.observer.start(start);
+        Steps {
+            optimizer,
+            obj,
+            rng,
+            start,
+            progress: Progress::new(start),
+            best: vec![],
+            tries: 0,
+            stop: None,
+            evaluator: Evaluator::full(),
+        }
+    }
+
+    /// Resumes stepping through the tries of an optimizer from a checkpoint.
+    ///
+    /// The object must be the initial object of the checkpointed optimization.
+    /// The modifier of the optimizer is replaced by the one of the checkpoint,
+    /// and the time limit counts the time spent before the checkpoint.
+    pub fn resume(
+        optimizer: &'a mut ModifyOptimizer<M, U, O>,
+        obj: &'a mut T,
+        checkpoint: Checkpoint<M::Change, M, R>
+    ) -> Self {
+        optimizer.modifier = checkpoint.modifier;
+        let start = optimizer.utility.utility(obj);
+        optimizer.observer.start(start);
+        let now = Instant::now();
+        Steps {
+            optimizer,
+            obj,
+            rng: checkpoint.rng,
+            start,
+            progress: Progress {
+                start: now.checked_sub(checkpoint.elapsed).unwrap_or(now),
+                evaluations: checkpoint.evaluations,
+                stagnation: checkpoint.stagnation,
+                best_utility: checkpoint.best_utility,
+            },
+            best: checkpoint.best,
+            tries: checkpoint.tries,
+            stop: None,
+            evaluator: Evaluator::full(),
+        }
+    }
+
+    /// Sets how utility is evaluated after each modification.
+    pub fn evaluate_by(mut self, evaluation: Evaluation) -> Self
+        where U: DeltaUtility<T, M::Change>
+    {
+        self.evaluator = Evaluator::new(evaluation);
+        self
+    }
+
+    /// Saves the state between tries, for resuming the optimization later.
+    pub fn checkpoint(&self) -> Checkpoint<M::Change, M, R>
+        where M: Clone, M::Change: Clone, R: Clone
+    {
+        Checkpoint {
+            tries: self.tries,
+            best: self.best.clone(),
+            best_utility: self.progress.best_utility,
+            evaluations: self.progress.evaluations,
+            stagnation: self.progress.stagnation,
+            elapsed: self.progress.elapsed(),
+            modifier: self.optimizer.modifier.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Returns the progress of the optimization.
+    pub fn progress(&self) -> &Progress {&self.progress}
+
+    /// Applies the best changes to the object and returns them with the reason for stopping.
+    ///
+    /// When stopping before the optimizer is done, the reason is `Stop::Interrupted`.
+    pub fn finish(self) -> (Vec<M::Change>, Stop) {
+        for change in &self.best {
+            self.optimizer.modifier.redo(change, self.obj);
+            self.optimizer.modifier.redo_meaning(change);
+        }
+        let stop = self.stop.unwrap_or(Stop::Interrupted);
+        self.optimizer.observer.stop(&stop);
+        (self.best, stop)
+    }
+}
+
+impl<'a, T, M, U, O, R> Iterator for Steps<'a, T, M, U, O, R>
+    where M: Modifier<T>, U: Utility<T>, O: Observer, R: RngCore, M::Change: Clone
+{
+    type Item = Step<M::Change>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stop.is_some() {return None}
+        if self.tries >= self.optimizer.tries {
+            self.stop = Some(Stop::Completed);
+            return None
+        }
+        let optimizer = &mut *self.optimizer;
+        let obj = &mut *self.obj;
+        optimizer.observer.next_try(self.tries);
+        let mut stack = vec![];
+        let mut improved = false;
+        let mut previous = self.start;
+        for _ in 0..optimizer.depth {
+            let change = optimizer.modifier.modify_with(obj, &mut self.rng);
+            optimizer.modifier.redo_meaning(&change);
+            let utility = self.evaluator.utility(&optimizer.utility, obj, &change, previous);
+            optimizer.modifier.feedback(&change, utility - previous);
+            previous = utility;
+            stack.push(change);
+            optimizer.observer.change(utility);
+            if self.progress.best_utility < utility {
+                self.best = stack.clone();
+                improved = true;
+                optimizer.observer.improve(utility);
+            }
+            self.progress.evaluate(utility);
+            self.stop = optimizer.termination.check(&self.progress);
+            if self.stop.is_some() {break}
+        }
+        optimizer.observer.backtrack(stack.len());
+        while let Some(ref action) = stack.pop() {
+            optimizer.modifier.undo(action, obj);
+            optimizer.modifier.undo_meaning(action);
+        }
+        self.tries += 1;
+        Some(Step {
+            tries: self.tries,
+            improved,
+            best_utility: self.progress.best_utility,
+            best: self.best.clone(),
+        })
+    }
+}
@@ -0,0 +1,200 @@
+//! Tabu search.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use rand::RngCore;
+
+use cache::hashed;
+use delta::{DeltaUtility, Evaluation, Evaluator};
+use {Modifier, Observer, Progress, Stop, Termination, Utility};
+
+/// Implemented by objects that extract a tabu key from a candidate move.
+///
+/// Candidates with keys in the tabu list are forbidden,
+/// unless they improve the best utility seen so far.
+pub trait TabuKey<T, C> {
+    /// Computes the key of a candidate, where the change has been applied to the object.
+    fn key(&self, obj: &T, change: &C) -> u64;
+    /// Computes the key of the initial object, if any.
+    ///
+    /// This is used to make the starting state tabu.
+    fn initial(&self, _obj: &T) -> Option<u64> {None}
+}
+
+/// Uses a hash of the object state as tabu key.
+///
+/// This forbids revisiting recently visited states.
+pub struct StateKey<F>(pub F);
+
+impl<T, C, F: Fn(&T) -> u64> TabuKey<T, C> for StateKey<F> {
+    fn key(&self, obj: &T, _change: &C) -> u64 {(self.0)(obj)}
+    fn initial(&self, obj: &T) -> Option<u64> {Some((self.0)(obj))}
+}
+
+/// Uses an attribute of the change as tabu key.
+///
+/// This forbids recently applied kinds of changes.
+pub struct ChangeKey<F>(pub F);
+
+impl<T, C, F: Fn(&C) -> u64> TabuKey<T, C> for ChangeKey<F> {
+    fn key(&self, _obj: &T, change: &C) -> u64 {(self.0)(change)}
+}
+
+/// Uses the standard hash of the object state as tabu key.
+pub struct Hashed;
+
+impl<T: Hash, C> TabuKey<T, C> for Hashed {
+    fn key(&self, obj: &T, _change: &C) -> u64 {hashed(obj)}
+    fn initial(&self, obj: &T) -> Option<u64> {Some(hashed(obj))}
+}
+
+/// Modifies an object by tabu search.
+///
+/// Each iteration samples a number of candidate modifications,
+/// evaluates them using `modify` and `undo`,
+/// and moves to the best candidate that is not tabu, even when it is worse.
+/// A tabu candidate is accepted if it improves the best utility (aspiration criterion).
+///
+/// The change is the sequence of changes leading to the best seen state.
+pub struct TabuSearch<M, U, K = Hashed, O = ()> {
+    /// The modifier to modify the object.
+    pub modifier: M,
+    /// The measured utility.
+    pub utility: U,
+    /// Extracts tabu keys from candidates.
+    pub key: K,
+    /// The maximum number of keys in the tabu list.
+    pub tenure: usize,
+    /// The number of candidates to sample per iteration.
+    pub neighbors: usize,
+    /// The number of iterations.
+    pub iterations: usize,
+    /// The criterion for stopping before all iterations are completed.
+    pub termination: Termination,
+    /// Observes the optimization.
+    ///
+    /// Evaluating a candidate is reported as a change.
+    pub observer: O,
+}
+
+impl<M, U, K, O: Observer> TabuSearch<M, U, K, O> {
+    /// Modifies an object and returns the change with the reason for stopping.
+    pub fn optimize<T>(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: Utility<T>, K: TabuKey<T, M::Change>
+    {
+        self.optimize_in(obj, rng, &Evaluator::full())
+    }
+
+    /// Modifies an object, evaluating utility by a strategy,
+    /// and returns the change with the reason for stopping.
+    pub fn optimize_by<T>(
+        &mut self,
+        obj: &mut T,
+        rng: &mut dyn RngCore,
+        evaluation: Evaluation
+    ) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: DeltaUtility<T, M::Change>, K: TabuKey<T, M::Change>
+    {
+        self.optimize_in(obj, rng, &Evaluator::new(evaluation))
+    }
+
+    fn optimize_in<T>(
+        &mut self,
+        obj: &mut T,
+        rng: &mut dyn RngCore,
+        evaluator: &Evaluator<U, T, M::Change>
+    ) -> (Vec<M::Change>, Stop)
+        where M: Modifier<T>, U: Utility<T>, K: TabuKey<T, M::Change>
+    {
+        let mut tabu: VecDeque<u64> = VecDeque::with_capacity(self.tenure);
+        if self.tenure > 0 {
+            if let Some(key) = self.key.initial(obj) {tabu.push_back(key)}
+        }
+        let mut best_utility = self.utility.utility(obj);
+        let mut current = best_utility;
+        self.observer.start(best_utility);
+        let mut progress = Progress::new(best_utility);
+        let mut best_len = 0;
+        let mut stack = vec![];
+        let mut stop = None;
+        for i in 0..self.iterations {
+            self.observer.next_try(i);
+            let mut candidate: Option<(f64, M::Change, u64)> = None;
+            for _ in 0..self.neighbors {
+                let change = self.modifier.modify_with(obj, rng);
+                self.modifier.redo_meaning(&change);
+                let utility = evaluator.utility(&self.utility, obj, &change, current);
+                self.modifier.feedback(&change, utility - current);
+                self.observer.change(utility);
+                let key = self.key.key(obj, &change);
+                self.modifier.undo(&change, obj);
+                self.modifier.undo_meaning(&change);
+                let admissible = !tabu.contains(&key) || best_utility < utility;
+                let better = match candidate {
+                    None => true,
+                    Some((u, _, _)) => u < utility,
+                };
+                if admissible && better {
+                    candidate = Some((utility, change, key));
+                }
+                progress.evaluate(utility);
+                stop = self.termination.check(&progress);
+                if stop.is_some() {break}
+            }
+            let (utility, change, key) = match candidate {
+                None => if stop.is_some() {break} else {continue},
+                Some(x) => x,
+            };
+            // When stopping, only move if it improves the best state.
+            if stop.is_some() && utility <= best_utility {break}
+            self.modifier.redo(&change, obj);
+            self.modifier.redo_meaning(&change);
+            stack.push(change);
+            current = utility;
+            if self.tenure > 0 {
+                if tabu.len() >= self.tenure {tabu.pop_front();}
+                tabu.push_back(key);
+            }
+            if best_utility < utility {
+                best_utility = utility;
+                best_len = stack.len();
+                self.observer.improve(utility);
+            }
+            if stop.is_some() {break}
+        }
+        self.observer.backtrack(stack.len() - best_len);
+        while stack.len() > best_len {
+            let change = stack.pop().unwrap();
+            self.modifier.undo(&change, obj);
+            self.modifier.undo_meaning(&change);
+        }
+        let stop = stop.unwrap_or(Stop::Completed);
+        self.observer.stop(&stop);
+        (stack, stop)
+    }
+}
+
+impl<T, M, U, K, O> Modifier<T> for TabuSearch<M, U, K, O>
+    where M: Modifier<T>, U: Utility<T>, K: TabuKey<T, M::Change>, O: Observer
+{
+    type Change = Vec<M::Change>;
+    fn modify(&mut self, obj: &mut T) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        self.optimize(obj, rng).0
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut T) {
+        for change in change.iter().rev() {
+            self.modifier.undo(change, obj);
+            self.modifier.undo_meaning(change);
+        }
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut T) {
+        for change in change {
+            self.modifier.redo(change, obj);
+            self.modifier.redo_meaning(change);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Termination criteria and evaluation budgets.
+
+use std::time::{Duration, Instant};
+
+/// Tracks the progress of an optimization run.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    /// The time when the run started.
+    pub start: Instant,
+    /// The number of utility evaluations.
+    pub evaluations: usize,
+    /// The number of utility evaluations since the last improvement.
+    pub stagnation: usize,
+    /// The best utility seen so far.
+    pub best_utility: f64,
+}
+
+impl Progress {
+    /// Starts tracking progress from the utility of the initial object.
+    ///
+    /// This counts as one utility evaluation.
+    pub fn new(utility: f64) -> Progress {
+        Progress {
+            start: Instant::now(),
+            evaluations: 1,
+            stagnation: 0,
+            best_utility: utility,
+        }
+    }
+
+    /// Records a utility evaluation.
+    pub fn evaluate(&mut self, utility: f64) {
+        self.evaluations += 1;
+        if self.best_utility < utility {
+            self.best_utility = utility;
+            self.stagnation = 0;
+        } else {
+            self.stagnation += 1;
+        }
+    }
+
+    /// Returns the time elapsed since the run started.
+    pub fn elapsed(&self) -> Duration {self.start.elapsed()}
+}
+
+/// Criterion for stopping an optimization run.
+#[derive(Clone, Debug, Default)]
+pub enum Termination {
+    /// Never stop before the optimizer completes.
+    #[default]
+    Never,
+    /// Stop when the time limit is reached.
+    Time(Duration),
+    /// Stop when the maximum number of utility evaluations is reached.
+    Evaluations(usize),
+    /// Stop when there has been no improvement for some number of utility evaluations.
+    Stagnation(usize),
+    /// Stop when the best utility reaches a target.
+    Target(f64),
+    /// Stop when any criterion is met.
+    Any(Vec<Termination>),
+    /// Stop when all criteria are met.
+    All(Vec<Termination>),
+}
+
+/// The reason an optimization run stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stop {
+    /// The optimizer completed without being stopped.
+    Completed,
+    /// The time limit was reached.
+    Time,
+    /// The maximum number of utility evaluations was reached.
+    Evaluations,
+    /// There was no improvement for too long.
+    Stagnation,
+    /// The target utility was reached.
+    Target,
+    /// All criteria were met.
+    All(Vec<Stop>),
+    /// The caller stopped the optimizer before it was done.
+    Interrupted,
+}
+
+impl Termination {
+    /// Combines with another criterion, stopping when any of them is met.
+    pub fn or(self, other: Termination) -> Termination {
+        match self {
+            Termination::Any(mut list) => {list.push(other); Termination::Any(list)}
+            x => Termination::Any(vec![x, other]),
+        }
+    }
+
+    /// Combines with another criterion, stopping when all of them are met.
+    pub fn and(self, other: Termination) -> Termination {
+        match self {
+            Termination::All(mut list) => {list.push(other); Termination::All(list)}
+            x => Termination::All(vec![x, other]),
+        }
+    }
+
+    /// Checks whether the run should stop, returning the criterion that was met.
+    pub fn check(&self, progress: &Progress) -> Option<Stop> {
+        match *self {
+            Termination::Never => None,
+            Termination::Time(limit) => {
+                if progress.elapsed() >= limit {Some(Stop::Time)} else {None}
+            }
+            Termination::Evaluations(n) => {
+                if progress.evaluations >= n {Some(Stop::Evaluations)} else {None}
+            }
+            Termination::Stagnation(n) => {
+                if progress.stagnation >= n {Some(Stop::Stagnation)} else {None}
+            }
+            Termination::Target(target) => {
+                if progress.best_utility >= target {Some(Stop::Target)} else {None}
+            }
+            Termination::Any(ref list) => {
+                list.iter().filter_map(|it| it.check(progress)).next()
+            }
+            Termination::All(ref list) => {
+                if list.is_empty() {return None}
+                let mut stops = Vec::with_capacity(list.len());
+                for it in list {
+                    stops.push(it.check(progress)?);
+                }
+                Some(Stop::All(stops))
+            }
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Checking laws of modifiers.
+//!
+//! This module requires the `testing` feature.
+//!
+//! Optimizers rely on `undo` exactly reversing a modification and `redo` being deterministic.
+//! `check` runs random sequences of modifications and verifies these laws,
+//! reporting a minimal failing sequence of changes.
+//! Composite modifiers, e.g. `Vec<M>` or `ModifyOptimizer`, are checked the same way,
+//! which verifies that their nested changes round-trip.
+//!
+//! `check_meaning` interleaves modifications, undos and redos of several modifiers
+//! sharing one object, and verifies that the meaning of each modifier,
+//! e.g. an index into a list, stays consistent with the object.
+
+use std::cell::Cell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+use {Generator, Modifier};
+
+/// Settings for checking laws.
+#[derive(Copy, Clone, Debug)]
+pub struct Laws {
+    /// The number of random sequences.
+    pub sequences: usize,
+    /// The number of modifications in each sequence.
+    pub length: usize,
+    /// The seed of the random number generator.
+    pub seed: u64,
+}
+
+impl Default for Laws {
+    fn default() -> Laws {Laws {sequences: 100, length: 20, seed: 0}}
+}
+
+/// A law of modifiers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Law {
+    /// `undo` after a modification restores the object.
+    Undo,
+    /// `redo` after `undo` reproduces the modified object.
+    Redo,
+    /// Undoing a sequence of changes in reverse order restores each previous object.
+    UndoSequence,
+    /// Redoing a sequence of changes reproduces each modified object.
+    RedoSequence,
+}
+
+impl fmt::Display for Law {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Law::Undo => "`undo` after a modification does not restore the object",
+            Law::Redo => "`redo` after `undo` does not reproduce the modified object",
+            Law::UndoSequence => "undoing a sequence of changes does not restore the object",
+            Law::RedoSequence => "redoing a sequence of changes does not reproduce the object",
+        })
+    }
+}
+
+/// Describes a violated law.
+#[derive(Clone, Debug)]
+pub struct Failure<T, C> {
+    /// The violated law.
+    pub law: Law,
+    /// The generated object.
+    pub initial: T,
+    /// A minimal sequence of changes violating the law, when redone on the initial object.
+    pub changes: Vec<C>,
+    /// The expected object.
+    pub expected: T,
+    /// The found object.
+    pub found: T,
+}
+
+impl<T: fmt::Debug, C: fmt::Debug> fmt::Display for Failure<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.law)?;
+        writeln!(f, "initial: {:?}", self.initial)?;
+        writeln!(f, "changes:")?;
+        for change in &self.changes {writeln!(f, "  {:?}", change)?}
+        writeln!(f, "expected: {:?}", self.expected)?;
+        write!(f, "found: {:?}", self.found)
+    }
+}
+
+/// Checks the laws of a modifier on random sequences of modifications.
+///
+/// Each sequence starts with a generated object and a clone of the modifier.
+/// When a law is violated, the sequence is shrunk by removing changes
+/// while replaying the rest with `redo` still violates a law.
+/// Panics during shrinking are caught, e.g. when a change no longer fits the object,
+/// and not reported by the panic hook of the current thread.
+/// Since the panic hook is shared by the process, it is replaced while shrinking,
+/// such that a hook set by another thread in the meantime is overwritten.
+pub fn check<T, G, M>(
+    generator: &mut G,
+    modifier: &M,
+    laws: &Laws
+) -> Result<(), Failure<T, M::Change>>
+    where T: Clone + PartialEq,
+          G: Generator<Output = T>,
+          M: Modifier<T> + Clone,
+          M::Change: Clone
+{
+    let mut rng = StdRng::seed_from_u64(laws.seed);
+    for _ in 0..laws.sequences {
+        let initial = generator.generate_with(&mut rng);
+        let mut changes = vec![];
+        let source = Source::Modify(&mut rng, laws.length);
+        if let Some((law, expected, found)) = run(&initial, modifier, &mut changes, source) {
+            let (law, changes, expected, found) =
+                shrink(&initial, modifier, changes, (law, expected, found));
+            return Err(Failure {law, initial, changes, expected, found});
+        }
+    }
+    Ok(())
+}
+
+/// Checks the laws of a modifier and panics with a report on failure.
+pub fn assert_laws<T, G, M>(generator: &mut G, modifier: &M, laws: &Laws)
+    where T: Clone + PartialEq + fmt::Debug,
+          G: Generator<Output = T>,
+          M: Modifier<T> + Clone,
+          M::Change: Clone + fmt::Debug
+{
+    if let Err(failure) = check(generator, modifier, laws) {
+        panic!("{}", failure)
+    }
+}
+
+/// The source of changes in a sequence.
+enum Source<'a> {
+    /// Modifies the object a number of times.
+    Modify(&'a mut dyn RngCore, usize),
+    /// Redoes given changes.
+    Redo,
+}
+
+type Violation<T> = (Law, T, T);
+
+/// Runs a sequence of changes and returns the first violated law,
+/// with the expected and found object.
+///
+/// When modifying, the changes are pushed before checking laws.
+fn run<T, M>(
+    initial: &T,
+    modifier: &M,
+    changes: &mut Vec<M::Change>,
+    source: Source
+) -> Option<Violation<T>>
+    where T: Clone + PartialEq, M: Modifier<T> + Clone
+{
+    let mut modifier = modifier.clone();
+    let mut obj = initial.clone();
+    let mut states = vec![initial.clone()];
+    let (mut rng, length) = match source {
+        Source::Modify(rng, length) => (Some(rng), length),
+        Source::Redo => (None, changes.len()),
+    };
+    for i in 0..length {
+        match rng {
+            Some(ref mut rng) => {
+                let change = modifier.modify_with(&mut obj, &mut **rng);
+                changes.push(change);
+            }
+            None => modifier.redo(&changes[i], &mut obj),
+        }
+        let change = &changes[i];
+        modifier.redo_meaning(change);
+        let after = obj.clone();
+        modifier.undo(change, &mut obj);
+        if obj != states[i] {return Some((Law::Undo, states[i].clone(), obj))}
+        modifier.undo_meaning(change);
+        modifier.redo(change, &mut obj);
+        if obj != after {return Some((Law::Redo, after, obj))}
+        modifier.redo_meaning(change);
+        states.push(after);
+    }
+    for i in (0..length).rev() {
+        modifier.undo(&changes[i], &mut obj);
+        modifier.undo_meaning(&changes[i]);
+        if obj != states[i] {return Some((Law::UndoSequence, states[i].clone(), obj))}
+    }
+    for i in 0..length {
+        modifier.redo(&changes[i], &mut obj);
+        modifier.redo_meaning(&changes[i]);
+        if obj != states[i + 1] {
+            return Some((Law::RedoSequence, states[i + 1].clone(), obj))
+        }
+    }
+    None
+}
+
+/// Removes changes greedily while the rest still violates a law.
+fn shrink<T, M>(
+    initial: &T,
+    modifier: &M,
+    mut changes: Vec<M::Change>,
+    mut violation: Violation<T>
+) -> (Law, Vec<M::Change>, T, T)
+    where T: Clone + PartialEq, M: Modifier<T> + Clone, M::Change: Clone
+{
+    quietly(|| {
+        let mut i = 0;
+        while i < changes.len() {
+            let mut candidate = changes.clone();
+            candidate.remove(i);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                run(initial, modifier, &mut candidate, Source::Redo)
+            }));
+            match result {
+                Ok(Some(found)) => {
+                    changes = candidate;
+                    violation = found;
+                }
+                _ => i += 1,
+            }
+        }
+    });
+    (violation.0, changes, violation.1, violation.2)
+}
+
+/// Serializes replacing the panic hook.
+static HOOK: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    /// Whether panics of the current thread are not reported.
+    static QUIET: Cell<bool> = const {Cell::new(false)};
+}
+
+/// Runs a closure without reporting its panics,
+/// while panics of other threads are reported by the previous panic hook.
+fn quietly<F: FnOnce()>(f: F) {
+    let _guard = HOOK.lock().unwrap_or_else(|err| err.into_inner());
+    let hook = Arc::new(panic::take_hook());
+    let previous = hook.clone();
+    panic::set_hook(Box::new(move |info| {
+        if !QUIET.with(|it| it.get()) {previous(info)}
+    }));
+    QUIET.with(|it| it.set(true));
+    f();
+    QUIET.with(|it| it.set(false));
+    drop(panic::take_hook());
+    match Arc::try_unwrap(hook) {
+        Ok(hook) => panic::set_hook(hook),
+        // Another thread holds the replaced hook.
+        Err(hook) => panic::set_hook(Box::new(move |info| hook(info))),
+    }
+}
+
+/// A step in an interleaving of modifiers sharing an object.
+#[derive(Clone, Debug)]
+pub enum Step<C> {
+    /// Modified the object using a modifier.
+    Modify(usize, C),
+    /// Undid the last change of a modifier.
+    Undo(usize, C),
+    /// Redid the last undone change of a modifier.
+    Redo(usize, C),
+}
+
+/// Describes a modifier whose meaning became inconsistent with the object.
+#[derive(Clone, Debug)]
+pub struct MeaningFailure<T, C> {
+    /// The index of the inconsistent modifier.
+    pub modifier: usize,
+    /// The generated object.
+    pub initial: T,
+    /// The steps leading to the inconsistency.
+    pub steps: Vec<Step<C>>,
+    /// The object after the steps.
+    pub found: T,
+}
+
+impl<T: fmt::Debug, C: fmt::Debug> fmt::Display for MeaningFailure<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "meaning of modifier {} is inconsistent with the object", self.modifier)?;
+        writeln!(f, "initial: {:?}", self.initial)?;
+        writeln!(f, "steps:")?;
+        for step in &self.steps {writeln!(f, "  {:?}", step)?}
+        write!(f, "found: {:?}", self.found)
+    }
+}
+
+/// Checks that modifiers sharing an object keep their meaning consistent with it.
+///
+/// Each sequence starts with a generated object and clones of the modifiers.
+/// Every step modifies the object using a random modifier,
+/// undoes the last change or redoes the last undone change,
+/// calling `redo_meaning` or `undo_meaning` on all modifiers, like an optimizer does.
+/// After each step, `consistent` is called for every modifier with the object.
+pub fn check_meaning<T, G, M, F>(
+    generator: &mut G,
+    modifiers: &[M],
+    consistent: F,
+    laws: &Laws
+) -> Result<(), MeaningFailure<T, M::Change>>
+    where T: Clone,
+          G: Generator<Output = T>,
+          M: Modifier<T> + Clone,
+          M::Change: Clone,
+          F: Fn(&M, &T) -> bool
+{
+    if modifiers.is_empty() {return Ok(())}
+    let mut rng = StdRng::seed_from_u64(laws.seed);
+    for _ in 0..laws.sequences {
+        let initial = generator.generate_with(&mut rng);
+        let mut obj = initial.clone();
+        let mut modifiers = modifiers.to_vec();
+        let mut done: Vec<(usize, M::Change)> = vec![];
+        let mut undone: Vec<(usize, M::Change)> = vec![];
+        let mut steps = vec![];
+        for _ in 0..laws.length {
+            let step = match rng.gen_range(0, 3) {
+                0 if !done.is_empty() => {
+                    let (i, change) = done.pop().unwrap();
+                    modifiers[i].undo(&change, &mut obj);
+                    for it in &mut modifiers {it.undo_meaning(&change)}
+                    undone.push((i, change.clone()));
+                    Step::Undo(i, change)
+                }
+                1 if !undone.is_empty() => {
+                    let (i, change) = undone.pop().unwrap();
+                    modifiers[i].redo(&change, &mut obj);
+                    for it in &mut modifiers {it.redo_meaning(&change)}
+                    done.push((i, change.clone()));
+                    Step::Redo(i, change)
+                }
+                _ => {
+                    let i = rng.gen_range(0, modifiers.len());
+                    let change = modifiers[i].modify_with(&mut obj, &mut rng);
+                    for it in &mut modifiers {it.redo_meaning(&change)}
+                    done.push((i, change.clone()));
+                    undone.clear();
+                    Step::Modify(i, change)
+                }
+            };
+            steps.push(step);
+            if let Some(modifier) = modifiers.iter().position(|it| !consistent(it, &obj)) {
+                return Err(MeaningFailure {modifier, initial, steps, found: obj});
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that modifiers keep their meaning and panics with a report on failure.
+pub fn assert_meaning<T, G, M, F>(generator: &mut G, modifiers: &[M], consistent: F, laws: &Laws)
+    where T: Clone + fmt::Debug,
+          G: Generator<Output = T>,
+          M: Modifier<T> + Clone,
+          M::Change: Clone + fmt::Debug,
+          F: Fn(&M, &T) -> bool
+{
+    if let Err(failure) = check_meaning(generator, modifiers, consistent, laws) {
+        panic!("{}", failure)
+    }
+}
@@ -0,0 +1,212 @@
+//! Reading traveling salesman problems in the TSPLIB format.
+//!
+//! Supports node coordinates with the edge weight types
+//! `EUC_2D`, `CEIL_2D`, `ATT` and `GEO`,
+//! and explicit edge weights with the formats
+//! `FULL_MATRIX`, `UPPER_ROW`, `LOWER_ROW`, `UPPER_DIAG_ROW` and `LOWER_DIAG_ROW`.
+//!
+//! Cities are numbered from zero.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use delta::DeltaUtility;
+use permutation::PermutationChange;
+use Utility;
+
+/// A symmetric traveling salesman problem.
+///
+/// The utility of a tour, a permutation of cities, is its negative length.
+#[derive(Clone, Debug)]
+pub struct Tsp {
+    /// The name of the problem.
+    pub name: String,
+    /// The number of cities.
+    pub dimension: usize,
+    /// The distance between each pair of cities, row by row.
+    pub distances: Vec<f64>,
+}
+
+impl Tsp {
+    /// Reads a problem from a file.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Tsp> {
+        Tsp::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a problem from text.
+    pub fn parse(text: &str) -> io::Result<Tsp> {
+        let mut name = String::new();
+        let mut dimension = None;
+        let mut weight_type = String::new();
+        let mut weight_format = String::from("FULL_MATRIX");
+        let mut coords = vec![];
+        let mut weights = vec![];
+        let mut section = "";
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {continue}
+            if line == "EOF" {break}
+            if line.ends_with("_SECTION") {
+                section = match line {
+                    "NODE_COORD_SECTION" => "coord",
+                    "EDGE_WEIGHT_SECTION" => "weight",
+                    _ => "",
+                };
+                continue
+            }
+            if let Some(colon) = line.find(':') {
+                let key = line[..colon].trim();
+                if key.chars().all(|c| c.is_ascii_uppercase() || c == '_') {
+                    let value = line[colon + 1..].trim().to_string();
+                    match key {
+                        "NAME" => name = value,
+                        "TYPE" if value != "TSP" => {
+                            return Err(invalid(format!("Unsupported type `{}`", value)))
+                        }
+                        "DIMENSION" => dimension = Some(number(&value)? as usize),
+                        "EDGE_WEIGHT_TYPE" => weight_type = value,
+                        "EDGE_WEIGHT_FORMAT" => weight_format = value,
+                        _ => {}
+                    }
+                    section = "";
+                    continue
+                }
+            }
+            match section {
+                "coord" => {
+                    let numbers = line.split_whitespace().map(number)
+                        .collect::<io::Result<Vec<f64>>>()?;
+                    if numbers.len() < 3 {
+                        return Err(invalid(format!("Expected coordinates, found `{}`", line)))
+                    }
+                    coords.push((numbers[1], numbers[2]));
+                }
+                "weight" => {
+                    for word in line.split_whitespace() {weights.push(number(word)?)}
+                }
+                _ => {}
+            }
+        }
+        let n = dimension.ok_or_else(|| invalid("Missing `DIMENSION`".into()))?;
+        let mut distances = vec![0.0; n * n];
+        if weight_type == "EXPLICIT" {
+            let mut words = weights.into_iter();
+            let mut next = || words.next().ok_or_else(|| invalid("Too few edge weights".into()));
+            for i in 0..n {
+                let range = match &weight_format[..] {
+                    "FULL_MATRIX" => 0..n,
+                    "UPPER_ROW" => i + 1..n,
+                    "LOWER_ROW" => 0..i,
+                    "UPPER_DIAG_ROW" => i..n,
+                    "LOWER_DIAG_ROW" => 0..i + 1,
+                    _ => return Err(invalid(format!("Unsupported format `{}`", weight_format))),
+                };
+                for j in range {
+                    let d = next()?;
+                    distances[i * n + j] = d;
+                    distances[j * n + i] = d;
+                }
+            }
+        } else {
+            if coords.len() != n {
+                return Err(invalid(format!("Expected {} coordinates, found {}", n, coords.len())))
+            }
+            let distance: fn((f64, f64), (f64, f64)) -> f64 = match &weight_type[..] {
+                "EUC_2D" => |a, b| euclidean(a, b).round(),
+                "CEIL_2D" => |a, b| euclidean(a, b).ceil(),
+                "ATT" => att,
+                "GEO" => geo,
+                _ => return Err(invalid(format!("Unsupported edge weight type `{}`", weight_type))),
+            };
+            for i in 0..n {
+                for j in 0..n {
+                    if i != j {distances[i * n + j] = distance(coords[i], coords[j])}
+                }
+            }
+        }
+        Ok(Tsp {name, dimension: n, distances})
+    }
+
+    /// Returns the distance between two cities.
+    pub fn distance(&self, a: usize, b: usize) -> f64 {
+        self.distances[a * self.dimension + b]
+    }
+
+    /// Computes the length of a closed tour.
+    pub fn tour_length(&self, tour: &[usize]) -> f64 {
+        if tour.is_empty() {return 0.0}
+        let back = self.distance(tour[tour.len() - 1], tour[0]);
+        tour.windows(2).map(|w| self.distance(w[0], w[1])).sum::<f64>() + back
+    }
+}
+
+impl Utility<Vec<usize>> for Tsp {
+    fn utility(&self, obj: &Vec<usize>) -> f64 {-self.tour_length(obj)}
+}
+
+/// Computes the delta of reversing and swapping from the edges at the ends,
+/// falling back to the full utility for other changes.
+impl DeltaUtility<Vec<usize>, PermutationChange> for Tsp {
+    fn delta(&self, obj: &Vec<usize>, change: &PermutationChange) -> Option<f64> {
+        let n = obj.len();
+        // Computes the change in length of the edges starting at some positions,
+        // given the city at each position before the change.
+        let edges = |starts: &[usize], before: &dyn Fn(usize) -> usize| {
+            let mut starts: Vec<usize> = starts.iter().map(|&i| i % n).collect();
+            starts.sort_unstable();
+            starts.dedup();
+            starts.iter().map(|&i| {
+                let j = (i + 1) % n;
+                self.distance(obj[i], obj[j]) - self.distance(obj[before(i)], obj[before(j)])
+            }).sum::<f64>()
+        };
+        match *change {
+            PermutationChange::Unchanged => Some(0.0),
+            PermutationChange::Reverse(a, b) => {
+                if b <= a + 1 {return Some(0.0)}
+                // Edges within the segment keep their length, since distances are symmetric.
+                let before = |i: usize| if a <= i && i < b {a + b - 1 - i} else {i};
+                Some(-edges(&[a + n - 1, b - 1], &before))
+            }
+            PermutationChange::Swap(a, b) => {
+                let before = |i: usize| if i == a {b} else if i == b {a} else {i};
+                Some(-edges(&[a + n - 1, a, b + n - 1, b], &before))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn number(word: &str) -> io::Result<f64> {
+    word.parse().map_err(|_| invalid(format!("Expected number, found `{}`", word)))
+}
+
+fn euclidean(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+fn att(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let r = (((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)) / 10.0).sqrt();
+    let t = r.round();
+    if t < r {t + 1.0} else {t}
+}
+
+fn geo(a: (f64, f64), b: (f64, f64)) -> f64 {
+    // TSPLIB defines distances with this approximation of pi.
+    #[allow(clippy::approx_constant)]
+    fn radians(x: f64) -> f64 {
+        let degrees = x.trunc();
+        3.141592 * (degrees + 5.0 * (x - degrees) / 3.0) / 180.0
+    }
+    let (lat_a, long_a) = (radians(a.0), radians(a.1));
+    let (lat_b, long_b) = (radians(b.0), radians(b.1));
+    let q1 = (long_a - long_b).cos();
+    let q2 = (lat_a - lat_b).cos();
+    let q3 = (lat_a + lat_b).cos();
+    (6378.388 * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+}
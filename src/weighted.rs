@@ -0,0 +1,86 @@
+//! Weighted random selection.
+
+use rand::{Rng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use float;
+use {Generator, Modifier, Utility};
+
+/// A list of items with weights.
+///
+/// - `Weighted<T: Utility>` sums the weighted utility of each sub-utility
+/// - `Weighted<T: Generator>` picks a generator with probability proportional to weight
+/// - `Weighted<T: Modifier>` picks a modifier with probability proportional to weight
+///
+/// Weights are required to be non-negative, with a positive sum.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "T: Serialize",
+    deserialize = "T: Deserialize<'de>"
+)))]
+pub struct Weighted<T>(
+    #[cfg_attr(feature = "serde", serde(with = "float::weights"))]
+    pub Vec<(f64, T)>
+);
+
+impl<T> Weighted<T> {
+    /// Picks a random index with probability proportional to weight.
+    pub fn pick(&self, rng: &mut dyn RngCore) -> usize {
+        let total: f64 = self.0.iter().map(|it| it.0).sum();
+        let mut r = rng.gen::<f64>() * total;
+        let mut last = 0;
+        for (i, &(weight, _)) in self.0.iter().enumerate() {
+            if weight <= 0.0 {continue}
+            if r < weight {return i}
+            r -= weight;
+            last = i;
+        }
+        last
+    }
+}
+
+impl<T, U: Utility<T>> Utility<T> for Weighted<U> {
+    fn utility(&self, obj: &T) -> f64 {
+        self.0.iter().map(|&(weight, ref it)| weight * it.utility(obj)).sum()
+    }
+}
+
+impl<T: Generator> Generator for Weighted<T> {
+    type Output = T::Output;
+    fn generate(&mut self) -> Self::Output {
+        self.generate_with(&mut ::rand::thread_rng())
+    }
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Self::Output {
+        let index = self.pick(rng);
+        self.0[index].1.generate_with(rng)
+    }
+}
+
+impl<T, U: Modifier<T>> Modifier<T> for Weighted<U> {
+    type Change = (usize, U::Change);
+    fn modify(&mut self, obj: &mut T) -> Self::Change {
+        self.modify_with(obj, &mut ::rand::thread_rng())
+    }
+    fn modify_with(&mut self, obj: &mut T, rng: &mut dyn RngCore) -> Self::Change {
+        let index = self.pick(rng);
+        (index, self.0[index].1.modify_with(obj, rng))
+    }
+    fn undo(&mut self, change: &Self::Change, obj: &mut T) {
+        self.0[change.0].1.undo(&change.1, obj)
+    }
+    fn redo(&mut self, change: &Self::Change, obj: &mut T) {
+        self.0[change.0].1.redo(&change.1, obj)
+    }
+    fn undo_meaning(&mut self, change: &Self::Change) {
+        for it in &mut self.0 {it.1.undo_meaning(&change.1)}
+    }
+    fn redo_meaning(&mut self, change: &Self::Change) {
+        for it in &mut self.0 {it.1.redo_meaning(&change.1)}
+    }
+    fn feedback(&mut self, change: &Self::Change, delta: f64) {
+        self.0[change.0].1.feedback(&change.1, delta)
+    }
+}
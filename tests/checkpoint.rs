@@ -0,0 +1,91 @@
+extern crate utility_programming as up;
+extern crate rand;
+extern crate serde_json;
+
+use std::f64;
+
+use rand::SeedableRng;
+use rand::prng::XorShiftRng;
+use up::adaptive::{AdaptiveModifier, Strategy};
+use up::numeric::{NumberChange, NumberOp};
+use up::{Checkpoint, NumberModifier};
+
+type Saved = Checkpoint<
+    (usize, NumberChange<i32>),
+    AdaptiveModifier<NumberModifier<i32>>,
+    XorShiftRng
+>;
+
+const CHANGE: (usize, NumberChange<i32>) = (0, NumberChange {old: 0, new: 1});
+
+fn checkpoint(best_utility: f64, total_delta: f64) -> Saved {
+    let mut modifier = AdaptiveModifier::new(vec![
+        NumberModifier::new(NumberOp::Increment, 0, 10),
+    ], Strategy::Thompson);
+    modifier.arms[0].total_delta = total_delta;
+    Checkpoint {
+        tries: 3,
+        best: vec![CHANGE],
+        best_utility,
+        evaluations: 10,
+        stagnation: 2,
+        elapsed: Default::default(),
+        modifier,
+        rng: XorShiftRng::seed_from_u64(0),
+    }
+}
+
+fn round_trip(checkpoint: &Saved) -> Saved {
+    let json = serde_json::to_string(checkpoint).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn finite_utility() {
+    let loaded = round_trip(&checkpoint(-2.5, 1.0));
+    assert_eq!(loaded.best_utility, -2.5);
+    assert_eq!(loaded.modifier.arms[0].total_delta, 1.0);
+    assert_eq!(loaded.best, vec![CHANGE]);
+}
+
+#[test]
+fn non_finite_utility() {
+    let loaded = round_trip(&checkpoint(f64::NEG_INFINITY, f64::INFINITY));
+    assert_eq!(loaded.best_utility, f64::NEG_INFINITY);
+    assert_eq!(loaded.modifier.arms[0].total_delta, f64::INFINITY);
+    assert!(round_trip(&checkpoint(f64::NAN, f64::NAN)).best_utility.is_nan());
+}
+
+#[test]
+fn non_finite_bounds() {
+    let mut modifier = AdaptiveModifier::new(vec![
+        NumberModifier::new(NumberOp::Gaussian, f64::NEG_INFINITY, f64::INFINITY).adaptive(1.5),
+    ], Strategy::Ucb1 {exploration: f64::INFINITY});
+    modifier.arms[0].quality = f64::NAN;
+    let change = (0, NumberChange {old: f64::INFINITY, new: f64::NAN});
+    let checkpoint = Checkpoint {
+        tries: 1,
+        best: vec![change],
+        best_utility: 0.0,
+        evaluations: 1,
+        stagnation: 0,
+        elapsed: Default::default(),
+        modifier,
+        rng: XorShiftRng::seed_from_u64(0),
+    };
+    let json = serde_json::to_string(&checkpoint).unwrap();
+    let loaded: Checkpoint<_, AdaptiveModifier<NumberModifier<f64>>, XorShiftRng> =
+        serde_json::from_str(&json).unwrap();
+    let number = &loaded.modifier.modifiers[0];
+    assert_eq!(number.min, f64::NEG_INFINITY);
+    assert_eq!(number.max, f64::INFINITY);
+    assert_eq!(number.adaptation, Some(1.5));
+    match loaded.modifier.strategy {
+        Strategy::Ucb1 {exploration} => assert_eq!(exploration, f64::INFINITY),
+        _ => panic!("expected UCB1"),
+    }
+    assert!(loaded.modifier.arms[0].quality.is_nan());
+    let (_, NumberChange {old, new}): (usize, NumberChange<f64>) = loaded.best[0];
+    assert_eq!(old, f64::INFINITY);
+    assert!(new.is_nan());
+}
@@ -0,0 +1,59 @@
+extern crate utility_programming as up;
+extern crate rand;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use up::adaptive::{AdaptiveModifier, Strategy};
+use up::constraint::Handling;
+use up::numeric::NumberOp;
+use up::{ConstrainedOptimizer, Constraint, NumberModifier, Termination, UtilityFn};
+
+/// Allows numbers up to a maximum.
+struct AtMost(i32);
+
+impl Constraint<i32> for AtMost {
+    fn violation(&self, obj: &i32) -> f64 {(*obj - self.0).max(0) as f64}
+}
+
+#[test]
+fn death_feeds_back_rejected_modifications() {
+    let mut optimizer = ConstrainedOptimizer {
+        modifier: AdaptiveModifier::new(vec![
+            NumberModifier::new(NumberOp::Increment, -1000, 1000),
+            NumberModifier::new(NumberOp::Decrement, -1000, 1000),
+        ], Strategy::Ucb1 {exploration: 1.0}),
+        utility: UtilityFn(|x: &i32| -*x as f64),
+        constraint: AtMost(10),
+        handling: Handling::Death,
+        tries: 100,
+        depth: 10,
+        termination: Termination::Never,
+        observer: (),
+    };
+    let mut x = 10;
+    let (changes, _) = optimizer.optimize(&mut x, &mut StdRng::seed_from_u64(0));
+    let arms = &optimizer.modifier.arms;
+    assert!(arms[0].pulls > 0);
+    assert_eq!(arms[0].successes, 0);
+    assert!(arms[1].pulls > arms[0].pulls);
+    assert!(!changes.is_empty());
+    assert!(x < 10);
+}
+
+#[test]
+fn stochastic_ranking_keeps_feasible_best() {
+    let mut optimizer = ConstrainedOptimizer {
+        modifier: NumberModifier::new(NumberOp::Increment, -1000, 1000),
+        utility: UtilityFn(|x: &i32| *x as f64),
+        constraint: AtMost(10),
+        handling: Handling::StochasticRanking(1.0),
+        tries: 10,
+        depth: 5,
+        termination: Termination::Never,
+        observer: (),
+    };
+    let mut x = 10;
+    let (changes, _) = optimizer.optimize(&mut x, &mut StdRng::seed_from_u64(0));
+    assert!(changes.is_empty());
+    assert_eq!(x, 10);
+}
@@ -0,0 +1,30 @@
+extern crate utility_programming as up;
+
+use up::list::{ListChange, ListOp};
+use up::{Focus, GeneratorFn, ListModifier, Modifier};
+
+struct Lists {
+    a: Vec<i32>,
+    b: Vec<i32>,
+}
+
+fn a(obj: &mut Lists) -> &mut Vec<i32> {&mut obj.a}
+fn b(obj: &mut Lists) -> &mut Vec<i32> {&mut obj.b}
+
+#[test]
+fn meaning_is_forwarded_to_the_same_part() {
+    let mut on_a = Focus::new(ListModifier::new(ListOp::Replace(GeneratorFn(|| 0))).within(2..3), a, 0);
+    let mut on_b = Focus::new(ListModifier::new(ListOp::Replace(GeneratorFn(|| 0))).within(2..3), b, 1);
+    let mut obj = Lists {a: vec![1, 2, 3], b: vec![1, 2, 3]};
+    let change = (0, ListChange::Insert {index: 0, value: 0});
+    on_a.redo(&change, &mut obj);
+    on_a.redo_meaning(&change);
+    on_b.redo_meaning(&change);
+    assert_eq!(obj.a, vec![0, 1, 2, 3]);
+    assert_eq!(on_a.0.range(), Some(3..4));
+    assert_eq!(on_b.0.range(), Some(2..3));
+    on_a.undo_meaning(&change);
+    on_b.undo_meaning(&change);
+    assert_eq!(on_a.0.range(), Some(2..3));
+    assert_eq!(on_b.0.range(), Some(2..3));
+}
@@ -0,0 +1,150 @@
+extern crate utility_programming as up;
+extern crate rand;
+
+use rand::{Rng, RngCore};
+use up::list::ListOp;
+use up::numeric::NumberOp;
+use up::permutation::RandomPermutation;
+use up::testing::{assert_laws, assert_meaning, check, Laws};
+use up::{
+    Generator, ListModifier, Modifier, ModifyOptimizer, NumberModifier,
+    PermutationModifier, Utility,
+};
+
+/// Generates a random small number.
+#[derive(Clone)]
+struct Small;
+
+impl Generator for Small {
+    type Output = i32;
+    fn generate(&mut self) -> i32 {self.generate_with(&mut rand::thread_rng())}
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> i32 {rng.gen_range(-10, 10)}
+}
+
+/// Generates a list of random small numbers.
+#[derive(Clone)]
+struct SmallList;
+
+impl Generator for SmallList {
+    type Output = Vec<i32>;
+    fn generate(&mut self) -> Vec<i32> {self.generate_with(&mut rand::thread_rng())}
+    fn generate_with(&mut self, rng: &mut dyn RngCore) -> Vec<i32> {
+        let n = rng.gen_range(0, 6);
+        (0..n).map(|_| Small.generate_with(rng)).collect()
+    }
+}
+
+/// Generates a fixed list.
+struct Fixed;
+
+impl Generator for Fixed {
+    type Output = Vec<i32>;
+    fn generate(&mut self) -> Vec<i32> {vec![0, 1, 2, 3, 4]}
+}
+
+/// Generates a constant.
+#[derive(Clone)]
+struct Constant(i32);
+
+impl Generator for Constant {
+    type Output = i32;
+    fn generate(&mut self) -> i32 {self.0}
+}
+
+/// Rewards a large sum.
+#[derive(Clone)]
+struct Sum;
+
+impl Utility<Vec<i32>> for Sum {
+    fn utility(&self, obj: &Vec<i32>) -> f64 {obj.iter().sum::<i32>() as f64}
+}
+
+/// Increments a number up to 3, but undoes by decrementing, which is wrong at the bound.
+#[derive(Clone)]
+struct Bounded;
+
+impl Modifier<i32> for Bounded {
+    type Change = ();
+    fn modify(&mut self, obj: &mut i32) {*obj = (*obj + 1).min(3)}
+    fn undo(&mut self, _: &(), obj: &mut i32) {*obj -= 1}
+    fn redo(&mut self, _: &(), obj: &mut i32) {*obj = (*obj + 1).min(3)}
+}
+
+fn list() -> Vec<ListModifier<Small>> {
+    vec![
+        ListModifier::new(ListOp::Insert(Small)),
+        ListModifier::new(ListOp::Remove),
+        ListModifier::new(ListOp::Swap),
+        ListModifier::new(ListOp::Move).within(1..3),
+        ListModifier::new(ListOp::Reverse),
+        ListModifier::new(ListOp::Replace(Small)),
+    ]
+}
+
+/// Returns `true` if the range of the replacing modifier holds the item `2`,
+/// or nothing if it was removed.
+fn holds_two(modifier: &ListModifier<Constant>, obj: &[i32]) -> bool {
+    match (&modifier.op, modifier.range()) {
+        (&ListOp::Replace(_), Some(range)) => {
+            range.end <= obj.len() && match range.len() {
+                0 => !obj.contains(&2),
+                1 => obj[range.start] == 2,
+                _ => false,
+            }
+        }
+        (&ListOp::Replace(_), None) => false,
+        _ => true,
+    }
+}
+
+#[test]
+fn list_modifiers() {
+    assert_laws(&mut SmallList, &list(), &Laws::default());
+}
+
+#[test]
+fn permutation_modifiers() {
+    let permutation = vec![
+        PermutationModifier::TwoOpt,
+        PermutationModifier::ThreeOpt,
+        PermutationModifier::OrOpt,
+        PermutationModifier::Swap,
+        PermutationModifier::Insertion,
+        PermutationModifier::Scramble(4),
+    ];
+    assert_laws(&mut RandomPermutation(8), &permutation, &Laws::default());
+}
+
+#[test]
+fn number_modifiers() {
+    let number = vec![
+        NumberModifier::new(NumberOp::Increment, -20, 20),
+        NumberModifier::new(NumberOp::Gaussian, -20, 20).sigma(3.0),
+        NumberModifier::new(NumberOp::BitFlip, i32::MIN, i32::MAX),
+    ];
+    assert_laws(&mut Small, &number, &Laws::default());
+}
+
+#[test]
+fn modify_optimizer() {
+    let optimizer = ModifyOptimizer::new(list(), Sum, 3, 4);
+    assert_laws(&mut SmallList, &optimizer, &Laws {sequences: 20, length: 5, ..Laws::default()});
+}
+
+#[test]
+fn list_modifiers_keep_holding_an_item() {
+    let laws = Laws::default();
+    let holding = vec![
+        ListModifier::new(ListOp::Insert(Constant(-1))),
+        ListModifier::new(ListOp::Remove),
+        ListModifier::new(ListOp::Move),
+        ListModifier::new(ListOp::Replace(Constant(2))).within(2..3),
+    ];
+    assert_meaning(&mut Fixed, &holding, |it, obj| holds_two(it, obj), &laws);
+    assert_meaning(&mut Fixed, &[holding], |it, obj| it.iter().all(|it| holds_two(it, obj)), &laws);
+}
+
+#[test]
+fn broken_modifier_fails() {
+    assert!(check(&mut Small, &Bounded, &Laws::default()).is_err());
+}
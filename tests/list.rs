@@ -0,0 +1,55 @@
+extern crate utility_programming as up;
+extern crate rand;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use up::list::{ListChange, ListOp};
+use up::{GeneratorFn, ListModifier, Modifier};
+
+#[test]
+fn insert_stays_inside_range() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let mut modifier = ListModifier::new(ListOp::Insert(GeneratorFn(|| 9))).within(1..3);
+        let mut obj = vec![0, 1, 2, 3];
+        let change = modifier.modify_with(&mut obj, &mut rng);
+        modifier.redo_meaning(&change);
+        match change {
+            ListChange::Insert {index, ..} => assert_eq!(index, 2),
+            _ => panic!("expected insert"),
+        }
+        assert_eq!(modifier.range(), Some(1..4));
+        assert_eq!(obj, vec![0, 1, 9, 2, 3]);
+    }
+}
+
+#[test]
+fn insert_needs_two_items_in_range() {
+    let mut modifier = ListModifier::new(ListOp::Insert(GeneratorFn(|| 9))).within(1..2);
+    let mut obj = vec![0, 1, 2];
+    let change = modifier.modify_with(&mut obj, &mut StdRng::seed_from_u64(0));
+    assert_eq!(change, ListChange::Unchanged);
+    assert_eq!(obj, vec![0, 1, 2]);
+}
+
+#[test]
+fn set_range_forgets_previous_ranges() {
+    let mut modifier = ListModifier::new(ListOp::Insert(GeneratorFn(|| 9))).within(1..3);
+    let change = ListChange::Remove {index: 1, value: 1};
+    Modifier::<Vec<i32>>::redo_meaning(&mut modifier, &change);
+    assert_eq!(modifier.range(), Some(1..2));
+    modifier.set_range(Some(2..4));
+    Modifier::<Vec<i32>>::undo_meaning(&mut modifier, &change);
+    assert_eq!(modifier.range(), Some(3..5));
+}
+
+#[test]
+fn undo_beyond_history_shifts_back() {
+    let mut modifier = ListModifier::new(ListOp::Insert(GeneratorFn(|| 9))).within(1..3);
+    let change = ListChange::Insert {index: 0, value: 0};
+    let n = 2 * up::list::HISTORY;
+    for _ in 0..n {Modifier::<Vec<i32>>::redo_meaning(&mut modifier, &change)}
+    assert_eq!(modifier.range(), Some(1 + n..3 + n));
+    for _ in 0..n {Modifier::<Vec<i32>>::undo_meaning(&mut modifier, &change)}
+    assert_eq!(modifier.range(), Some(1..3));
+}